@@ -11,5 +11,8 @@
       rustfmt
       clippy
       cargo-outdated
+      # python bindings
+      maturin
+      python3
     ];
 }
@@ -1,6 +1,23 @@
-use clap::{crate_authors, crate_version, Arg, Command};
+use clap::{crate_authors, crate_version, value_parser, Arg, ArgAction, Command};
+use prp_pre::heuristic::{self, Heuristic};
+use prp_pre::WitnessLimits;
 
-pub fn get_arguments() -> clap::error::Result<(String, String, f64, String)> {
+pub struct Arguments {
+    pub fmi_file: String,
+    pub mlp_file: String,
+    pub contraction_stop: f64,
+    pub output_file: String,
+    pub checkpoint_file: Option<String>,
+    pub checkpoint_every: Option<usize>,
+    pub resume: bool,
+    pub report_file: Option<String>,
+    pub heuristic: Box<dyn Heuristic>,
+    pub candidates: usize,
+    pub witness_limits: WitnessLimits,
+    pub cell_contraction: bool,
+}
+
+pub fn get_arguments() -> clap::error::Result<Arguments> {
     let matches = Command::new("prp-pre")
         .version(crate_version!())
         .author(crate_authors!())
@@ -28,9 +45,52 @@ pub fn get_arguments() -> clap::error::Result<(String, String, f64, String)> {
                 .num_args(1)
                 .short('p')
                 .long("contraction-stop")
+                .value_parser(value_parser!(f64))
                 .conflicts_with("mlp-file")
                 .required_unless_present("mlp-file"),
         )
+        .arg(
+            Arg::new("heuristic")
+                .help("order of contraction: degree, edge-difference, deleted-neighbors, depth or a weighted sum like edge-difference=2,deleted-neighbors=1")
+                .num_args(1)
+                .long("heuristic")
+                .default_value("degree")
+                .value_parser(|value: &str| heuristic::parse_heuristic(value).map(|_| value.to_string())),
+        )
+        .arg(
+            Arg::new("candidates")
+                .help("only the lowest 1/n of the remaining nodes are contracted per rank")
+                .num_args(1)
+                .long("candidates")
+                .default_value("10")
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("max-hops")
+                .help("witness searches follow at most this many edges, missed witnesses add shortcuts")
+                .num_args(1)
+                .long("max-hops")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("max-settled-nodes")
+                .help("witness searches settle at most this many nodes, missed witnesses add shortcuts")
+                .num_args(1)
+                .long("max-settled-nodes")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("cost-bound")
+                .help("stop witness searches at the cost of the paths over the contracted node")
+                .long("cost-bound")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("cell-contraction")
+                .help("contract the cells of the lower levels in parallel, can add shortcuts")
+                .long("cell-contraction")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("output-file")
                 .help("the output file")
@@ -39,6 +99,31 @@ pub fn get_arguments() -> clap::error::Result<(String, String, f64, String)> {
                 .long("output")
                 .required(true),
         )
+        .arg(
+            Arg::new("checkpoint-file")
+                .help("save the contraction state to this file, defaults to the output file with .checkpoint")
+                .num_args(1)
+                .long("checkpoint"),
+        )
+        .arg(
+            Arg::new("checkpoint-every")
+                .help("save the contraction state every this many ranks, besides after every level")
+                .num_args(1)
+                .long("checkpoint-every")
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("resume")
+                .help("continue from the checkpoint of an interrupted run")
+                .long("resume")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("report-file")
+                .help("statistics of the pre-computation as .json or per rank as .csv")
+                .num_args(1)
+                .long("report"),
+        )
         .get_matches();
 
     let fmi_file = matches
@@ -57,10 +142,41 @@ pub fn get_arguments() -> clap::error::Result<(String, String, f64, String)> {
         .get_one::<String>("output-file")
         .expect("`output-file` is required");
 
-    Ok((
-        fmi_file.to_string(),
-        mlp_file.to_string(),
+    let checkpoint_every = matches
+        .get_one::<u64>("checkpoint-every")
+        .map(|every| *every as usize);
+    let resume = matches.get_flag("resume");
+    // checkpoints are only written if asked for
+    let checkpoint_file = match matches.get_one::<String>("checkpoint-file") {
+        Some(file) => Some(file.to_string()),
+        None if checkpoint_every.is_some() || resume => Some(format!("{}.checkpoint", output_file)),
+        None => None,
+    };
+
+    // already validated by the value parser
+    let heuristic = matches
+        .get_one::<String>("heuristic")
+        .map(|value| heuristic::parse_heuristic(value).unwrap())
+        .expect("`heuristic` has a default");
+
+    Ok(Arguments {
+        fmi_file: fmi_file.to_string(),
+        mlp_file: mlp_file.to_string(),
         contraction_stop,
-        output_file.to_string(),
-    ))
+        output_file: output_file.to_string(),
+        checkpoint_file,
+        checkpoint_every,
+        resume,
+        report_file: matches.get_one::<String>("report-file").cloned(),
+        heuristic,
+        candidates: *matches
+            .get_one::<u64>("candidates")
+            .expect("`candidates` has a default") as usize,
+        witness_limits: WitnessLimits {
+            max_hops: matches.get_one::<usize>("max-hops").copied(),
+            max_settled_nodes: matches.get_one::<usize>("max-settled-nodes").copied(),
+            cost_bound: matches.get_flag("cost-bound"),
+        },
+        cell_contraction: matches.get_flag("cell-contraction"),
+    })
 }
@@ -0,0 +1,41 @@
+use clap::{crate_authors, crate_version, Arg, Command};
+use prp_pre::{export, upgrade};
+
+/// converts bin-files written before the format version into the current format
+fn main() {
+    let matches = Command::new("prp-upgrade")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("converts bin-files of older versions without pre-processing again")
+        .arg(
+            Arg::new("input-file")
+                .help("the old bin-file")
+                .num_args(1)
+                .short('i')
+                .long("input")
+                .required(true),
+        )
+        .arg(
+            Arg::new("output-file")
+                .help("the converted bin-file")
+                .num_args(1)
+                .short('o')
+                .long("output")
+                .required(true),
+        )
+        .get_matches();
+    let input_file = matches.get_one::<String>("input-file").unwrap();
+    let output_file = matches.get_one::<String>("output-file").unwrap();
+
+    let bin_file = match upgrade::read_legacy_file(input_file) {
+        Ok(result) => {
+            println!("reading old bin file finished");
+            result
+        }
+        Err(error) => panic!("error while reading old bin file: {:?}", error),
+    };
+    match export::write_to_disk(output_file, &bin_file) {
+        Ok(_result) => println!("writing bin file finished"),
+        Err(error) => panic!("error while writing bin file: {:?}", error),
+    };
+}
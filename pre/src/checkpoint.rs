@@ -0,0 +1,207 @@
+use super::*;
+use contraction::ContractionState;
+use heuristic::NodeOrdering;
+use ndijkstra::WitnessLimits;
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+
+/// checkpoint of a running contraction, borrowing everything from it
+#[derive(Serialize)]
+pub struct CheckpointRef<'a> {
+    amount_nodes: usize,
+    graph_hash: u64,
+    mlp_levels: &'a [usize],
+    contraction_stop: f64,
+    heuristic: String,
+    candidates: usize,
+    witness_limits: WitnessLimits,
+    cell_contraction: bool,
+    state: &'a ContractionState,
+    ranks: Vec<Rank>,
+    #[serde(with = "full_edges")]
+    edges: &'a [Edge],
+    up_offset: &'a [EdgeId],
+    down_offset: &'a [EdgeId],
+    down_index: &'a [EdgeId],
+}
+
+impl<'a> CheckpointRef<'a> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        state: &'a ContractionState,
+        graph_hash: u64,
+        nodes: &[Node],
+        edges: &'a [Edge],
+        up_offset: &'a [EdgeId],
+        down_offset: &'a [EdgeId],
+        down_index: &'a [EdgeId],
+        mlp_levels: &'a [usize],
+        contraction_stop: f64,
+        node_ordering: &NodeOrdering,
+        witness_limits: WitnessLimits,
+        cell_contraction: bool,
+    ) -> Self {
+        CheckpointRef {
+            amount_nodes: nodes.len(),
+            graph_hash,
+            mlp_levels,
+            contraction_stop,
+            heuristic: node_ordering.heuristic.name(),
+            candidates: node_ordering.candidates,
+            witness_limits,
+            cell_contraction,
+            state,
+            ranks: nodes.iter().map(|node| node.rank).collect(),
+            edges,
+            up_offset,
+            down_offset,
+            down_index,
+        }
+    }
+}
+
+/// checkpoint read from disk, the fields have the same order as in `CheckpointRef`
+#[derive(Deserialize)]
+pub struct Checkpoint {
+    amount_nodes: usize,
+    graph_hash: u64,
+    mlp_levels: Vec<usize>,
+    contraction_stop: f64,
+    heuristic: String,
+    candidates: usize,
+    witness_limits: WitnessLimits,
+    cell_contraction: bool,
+    state: ContractionState,
+    ranks: Vec<Rank>,
+    #[serde(with = "full_edges")]
+    edges: Vec<Edge>,
+    up_offset: Vec<EdgeId>,
+    down_offset: Vec<EdgeId>,
+    down_index: Vec<EdgeId>,
+}
+
+impl Checkpoint {
+    pub fn read_file(file_path: &str) -> bincode::Result<Self> {
+        let reader = BufReader::new(File::open(file_path)?);
+        bincode::deserialize_from(reader)
+    }
+
+    /// the checkpoint was written while contracting the same graph with the same options
+    #[allow(clippy::too_many_arguments)]
+    pub fn matches(
+        &self,
+        amount_nodes: usize,
+        graph_hash: u64,
+        mlp_levels: &[usize],
+        contraction_stop: f64,
+        node_ordering: &NodeOrdering,
+        witness_limits: WitnessLimits,
+        cell_contraction: bool,
+    ) -> bool {
+        self.amount_nodes == amount_nodes
+            && self.graph_hash == graph_hash
+            && self.mlp_levels == mlp_levels
+            && self.contraction_stop == contraction_stop
+            && self.heuristic == node_ordering.heuristic.name()
+            && self.candidates == node_ordering.candidates
+            && self.witness_limits == witness_limits
+            && self.cell_contraction == cell_contraction
+    }
+
+    /// put the graph back into the state of the checkpoint
+    pub fn restore(
+        self,
+        nodes: &mut [Node],
+        edges: &mut Vec<Edge>,
+        up_offset: &mut Vec<EdgeId>,
+        down_offset: &mut Vec<EdgeId>,
+        down_index: &mut Vec<EdgeId>,
+    ) -> ContractionState {
+        for (node, rank) in nodes.iter_mut().zip(self.ranks) {
+            node.rank = rank;
+        }
+        *edges = self.edges;
+        *up_offset = self.up_offset;
+        *down_offset = self.down_offset;
+        *down_index = self.down_index;
+        self.state
+    }
+}
+
+/// fingerprint of the edges, their costs and the partition of a graph, stable between runs
+pub fn graph_hash(edges: &[Edge], partitions: &[PartitionId]) -> u64 {
+    // fnv-1a, the hasher of std may change between rust versions
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut add = |value: u64| {
+        for byte in value.to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    add(edges.len() as u64);
+    for edge in edges {
+        add(edge.from as u64);
+        add(edge.to as u64);
+        edge.cost.iter().for_each(|cost| add(cost.to_bits()));
+    }
+    add(partitions.len() as u64);
+    partitions
+        .iter()
+        .for_each(|partition| add(*partition as u64));
+    hash
+}
+
+/// write into a temporary file first, so a crash while writing keeps the previous checkpoint
+pub fn write_file(file_path: &str, checkpoint: &CheckpointRef) -> bincode::Result<()> {
+    let tmp_path = format!("{}.tmp", file_path);
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    bincode::serialize_into(&mut writer, checkpoint)?;
+    let file = writer.into_inner().map_err(|error| error.into_error())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, file_path)?;
+    Ok(())
+}
+
+/// edges including the id and costs, which the bin-file does not contain
+pub mod full_edges {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    type FullEdge = (
+        NodeId,
+        NodeId,
+        Option<EdgeId>,
+        Vec<Cost>,
+        Option<Level>,
+        Option<(EdgeId, EdgeId)>,
+    );
+
+    pub fn serialize<S: Serializer>(edges: &[Edge], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(edges.iter().map(|edge| {
+            (
+                edge.from,
+                edge.to,
+                edge.id,
+                &edge.cost,
+                edge.level,
+                edge.contracted_edges,
+            )
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Edge>, D::Error> {
+        let edges = Vec::<FullEdge>::deserialize(deserializer)?;
+        Ok(edges
+            .into_iter()
+            .map(|(from, to, id, cost, level, contracted_edges)| Edge {
+                from,
+                to,
+                id,
+                cost,
+                level,
+                contracted_edges,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,133 @@
+use super::*;
+
+/// strongly connected components using an iterative tarjan.
+/// ids are assigned in reverse topological order of the condensed graph,
+/// so an edge between components always leads to a smaller or equal id
+pub fn strongly_connected(
+    amount_nodes: usize,
+    edges: &[Edge],
+    up_offset: &[EdgeId],
+) -> Vec<ComponentId> {
+    let mut components = vec![INVALID_COMPONENT; amount_nodes];
+    let mut index = vec![usize::MAX; amount_nodes];
+    let mut low_link = vec![0; amount_nodes];
+    let mut on_stack = vec![false; amount_nodes];
+    let mut stack: Vec<NodeId> = Vec::new();
+    // node and position of its next outgoing edge
+    let mut call_stack: Vec<(NodeId, EdgeId)> = Vec::new();
+    let mut next_index = 0;
+    let mut next_component = 0;
+
+    for root in 0..amount_nodes {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        call_stack.push((root, up_offset[root]));
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, edge_id)) = call_stack.pop() {
+            if edge_id < up_offset[node + 1] {
+                call_stack.push((node, edge_id + 1));
+                let next = edges[edge_id].to;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, up_offset[next]));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+
+            // all edges done, node is root of a component
+            if low_link[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    components[member] = next_component;
+                    if member == node {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+            if let Some((parent, _)) = call_stack.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[node]);
+            }
+        }
+    }
+    components
+}
+
+/// components ignoring the direction of edges, numbered by their smallest node
+pub fn weakly_connected(amount_nodes: usize, edges: &[Edge]) -> Vec<ComponentId> {
+    let mut parents: Vec<NodeId> = (0..amount_nodes).collect();
+
+    fn find(parents: &mut [NodeId], node: NodeId) -> NodeId {
+        let mut root = node;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        // path compression
+        let mut current = node;
+        while parents[current] != root {
+            let next = parents[current];
+            parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    for edge in edges {
+        let from = find(&mut parents, edge.from);
+        let to = find(&mut parents, edge.to);
+        if from != to {
+            parents[from.max(to)] = from.min(to);
+        }
+    }
+
+    let mut ids = vec![INVALID_COMPONENT; amount_nodes];
+    let mut next_component = 0;
+    (0..amount_nodes)
+        .map(|node| {
+            let root = find(&mut parents, node);
+            if ids[root] == INVALID_COMPONENT {
+                ids[root] = next_component;
+                next_component += 1;
+            }
+            ids[root]
+        })
+        .collect()
+}
+
+#[test]
+fn components() {
+    // 0 <-> 1 -> 2 <-> 3, 4 alone
+    let mut edges = vec![
+        Edge::new(0, 1, vec![1.0]),
+        Edge::new(1, 0, vec![1.0]),
+        Edge::new(1, 2, vec![1.0]),
+        Edge::new(2, 3, vec![1.0]),
+        Edge::new(3, 2, vec![1.0]),
+    ];
+    let mut up_offset = Vec::<EdgeId>::new();
+    let mut down_offset = Vec::<EdgeId>::new();
+    offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, 5);
+
+    let strong = strongly_connected(5, &edges, &up_offset);
+    assert_eq!(strong[0], strong[1]);
+    assert_eq!(strong[2], strong[3]);
+    assert_ne!(strong[1], strong[2]);
+    assert_ne!(strong[4], strong[0]);
+    // reachable components never have a larger id
+    assert!(strong[0] > strong[2]);
+
+    let weak = weakly_connected(5, &edges);
+    assert_eq!(weak, vec![0, 0, 0, 0, 1]);
+}
@@ -5,6 +5,7 @@ pub type Rank = usize;
 pub type PartitionId = usize;
 pub type Level = usize;
 pub type GridId = usize;
+pub type ComponentId = usize;
 pub type Angle = f64;
 
 #[allow(dead_code)]
@@ -14,8 +15,17 @@ pub const INVALID_EDGE: NodeId = usize::MAX;
 pub const INVALID_RANK: Rank = usize::MAX;
 pub const INVALID_PARTITION: PartitionId = usize::MAX;
 pub const INVALID_LEVEL: Level = usize::MAX;
+pub const INVALID_COMPONENT: ComponentId = usize::MAX;
 pub const COST_MAX: Cost = f64::MAX;
 
 // ratio: north south 876km / west east 640 km ~ 100:136
 pub const LAT_GRID_AMOUNT: usize = 136;
 pub const LNG_GRID_AMOUNT: usize = 100;
+
+// every bin-file starts with the magic and its format version,
+// increase the version whenever the layout of `BinFile` changes.
+// 2 is the first versioned format, there is no version 1:
+// older bin-files have no header at all and are converted by prp_upgrade.
+// prp_query reads the bin-files with these constants as well
+pub const BIN_FILE_MAGIC: [u8; 8] = *b"PRPBIN\0\0";
+pub const BIN_FILE_VERSION: u32 = 2;
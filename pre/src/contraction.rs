@@ -1,13 +1,20 @@
 use super::*;
-use ndijkstra::NDijkstra;
+use checkpoint::{Checkpoint, CheckpointRef};
+use heuristic::{NodeOrdering, OrderingGraph, Priority};
+use ndijkstra::{NDijkstra, WitnessLimit, WitnessLimits};
 use valid_flag::ValidFlag;
 
 use crossbeam_channel::bounded;
-use mch::same_array;
+use mch::{costs_by_alpha, same_array};
+use report::RankReport;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::cmp::Reverse;
 use std::collections::BTreeSet;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
+use std::time::Instant;
+
+type MchEdge = mch::Edge<EdgeId, NodeId>;
 
 fn sort_nodes_ranked(
     edges: &mut Vec<Edge>,
@@ -42,7 +49,7 @@ fn sort_nodes_ranked(
 }
 
 fn sort_edges_ranked(
-    edges: &mut Vec<Edge>,
+    edges: &mut [Edge],
     down_offset: &[EdgeId],
     down_index: &mut Vec<EdgeId>,
     nodes: &[Node],
@@ -105,225 +112,604 @@ fn revert_indices(edges: &mut Vec<Edge>) {
     }
 }
 
-// contract one level until end
+/// everything of a running contraction, that is not stored in the graph itself
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ContractionState {
+    /// level currently contracted
+    pub level: Level,
+    /// remaining nodes and heuristics are set up for `level`
+    pub level_started: bool,
+    pub rank: Rank,
+    pub remaining_nodes: BTreeSet<NodeId>,
+    pub contracted_nodes_amount: usize,
+    pub heuristics: Vec<Priority>,
+    pub deleted_neighbors: Vec<usize>,
+    /// one more than the deepest contracted neighbor
+    pub depths: Vec<usize>,
+    /// id of the next shortcut
+    pub shortcut_id: EdgeId,
+    /// edges of contracted nodes, that are no longer part of the remaining graph
+    #[serde(with = "crate::checkpoint::full_edges")]
+    pub resulting_edges: Vec<Edge>,
+    /// statistics of every contracted rank so far
+    pub ranks: Vec<RankReport>,
+}
+
+// contract one independent set of the remaining nodes
 #[allow(clippy::too_many_arguments)]
-fn level_contraction(
-    level: Level,
-    remaining_nodes: &mut BTreeSet<NodeId>,
+fn contract_independent_set(
+    state: &mut ContractionState,
+    node_ordering: &NodeOrdering,
+    witness_limits: WitnessLimits,
     independent_set_flags: &mut ValidFlag,
-    heuristics: &mut [usize],
-    deleted_neighbors: &mut [usize],
-    shortcut_id: &AtomicUsize,
-    resulting_edges: &mut Vec<Edge>,
-    rank: &mut Rank,
     nodes: &mut [Node],
     edges: &mut Vec<Edge>,
     up_offset: &mut Vec<EdgeId>,
     down_offset: &mut Vec<EdgeId>,
     down_index: &mut Vec<EdgeId>,
-    mut contracted_nodes_amount: usize,
-    contraction_stop: f64,
+    dim: usize,
+    thread_count: usize,
 ) {
-    let dim = edges[0].cost.len();
+    let rank_time = Instant::now();
     let amount_nodes = nodes.len();
 
-    let thread_count = num_cpus::get();
-
-    while !(remaining_nodes.is_empty()
-        || (contracted_nodes_amount as f64 / nodes.len() as f64) > contraction_stop)
-    {
-        // I ← independent node set
-        let minimas = ordering::get_independent_set(
-            remaining_nodes,
-            heuristics,
-            independent_set_flags,
-            edges,
-            up_offset,
-            down_offset,
-            down_index,
-        );
-
-        // REMINDER: the break is allowed and does not mess up correctness only on the top layer
-        // if minimas.len() == 1 {
-        //     break;
-        // }
-
-        // E ← necessary shortcuts
-
-        // channels for sending and vector for collecting results
-        let (node_send, node_recieve) = bounded(0);
-        let parallel_shortcuts: Mutex<Vec<Edge>> =
-            Mutex::new(Vec::with_capacity(dim * minimas.len()));
-
-        rayon::scope(|s| {
-            for _thread in 0..thread_count {
-                s.spawn(|_| {
-                    let mut dijkstra = NDijkstra::new(amount_nodes, dim);
-                    // REMINDER: check process-fork slow down
-                    let mut mch = match mch::Contractor::new(
-                        // dijkstra
-                        |start, end, alpha| -> Vec<Cost> {
-                            match dijkstra.find_path(start, end, alpha.to_vec(), up_offset, edges) {
-                                Some(costs) => costs.1,
-                                None => vec![COST_MAX; edges[0].cost.len()],
+    // I ← independent node set
+    let minimas = ordering::get_independent_set(
+        &state.remaining_nodes,
+        &state.heuristics,
+        node_ordering.candidates,
+        independent_set_flags,
+        edges,
+        up_offset,
+        down_offset,
+        down_index,
+    );
+
+    // REMINDER: the break is allowed and does not mess up correctness only on the top layer
+    // if minimas.len() == 1 {
+    //     break;
+    // }
+
+    // E ← necessary shortcuts
+
+    // channels for sending and vector for collecting results
+    let (node_send, node_recieve) = bounded::<(usize, MchEdge, Vec<MchEdge>)>(0);
+    let parallel_shortcuts: Mutex<Vec<(usize, Edge, Vec<WitnessLimit>)>> =
+        Mutex::new(Vec::with_capacity(dim * minimas.len()));
+
+    // plain threads, a rayon scope would block its only worker on a single core
+    std::thread::scope(|s| {
+        for _thread in 0..thread_count {
+            s.spawn(|| {
+                let dijkstra =
+                    RefCell::new(NDijkstra::with_limits(amount_nodes, dim, witness_limits));
+                // incoming edge and all outgoing edges of the contracted node
+                let witness_targets: RefCell<Option<(MchEdge, Vec<MchEdge>)>> = RefCell::new(None);
+                let limited_by = RefCell::new(Vec::<WitnessLimit>::new());
+                // REMINDER: check process-fork slow down
+                let mut mch = match mch::Contractor::new(
+                    // dijkstra
+                    |start, end, alpha| -> Vec<Cost> {
+                        let mut dijkstra = dijkstra.borrow_mut();
+                        // one search for all out-neighbours of the contracted node,
+                        // bounded by the most expensive path over it
+                        if let Some((to, froms)) = witness_targets.borrow().as_ref() {
+                            if start == to.from && !dijkstra.is_searching(start, alpha) {
+                                let to_cost = costs_by_alpha(&to.cost, alpha);
+                                let max_cost = froms
+                                    .iter()
+                                    .map(|from| to_cost + costs_by_alpha(&from.cost, alpha))
+                                    .fold(0.0, Cost::max);
+                                let targets: Vec<NodeId> =
+                                    froms.iter().map(|from| from.to).collect();
+                                dijkstra.find_paths(
+                                    start,
+                                    &targets,
+                                    alpha.to_vec(),
+                                    max_cost,
+                                    up_offset,
+                                    edges,
+                                );
                             }
-                        },
-                        // to-edges
-                        |node_id| -> Vec<mch::Edge<EdgeId, NodeId>> {
-                            let down_edge_ids =
-                                graph_helper::get_down_edge_ids(node_id, down_offset, down_index);
-                            let mut mch_edges = Vec::new();
-                            for down_edge_id in down_edge_ids {
-                                let edge = &edges[down_edge_id];
-                                mch_edges.push(mch::Edge::new(
-                                    edge.id.unwrap(),
-                                    edge.from,
-                                    edge.to,
-                                    edge.cost.clone(),
-                                ))
-                            }
-                            mch_edges
-                        },
-                        // from-edges
-                        |node_id| -> Vec<mch::Edge<EdgeId, NodeId>> {
-                            let up_edge_ids = graph_helper::get_up_edge_ids(node_id, up_offset);
-                            let mut mch_edges = Vec::new();
-                            for up_edge_id in up_edge_ids {
-                                let edge = &edges[up_edge_id];
-                                mch_edges.push(mch::Edge::new(
-                                    edge.id.unwrap(),
-                                    edge.from,
-                                    edge.to,
-                                    edge.cost.clone(),
-                                ))
-                            }
-                            mch_edges
-                        },
-                        //dims
-                        dim,
-                    ) {
-                        Ok(mch) => mch,
-                        Err(error) => {
-                            panic!("error with mch: '{:?}'", error);
                         }
-                    };
-
-                    let mut thread_shortcuts =
-                        Vec::with_capacity((dim * minimas.len()) / thread_count);
-                    while let Ok((to, from)) = node_recieve.recv() {
-                        let mch_shortcut = match mch.shortcuts(&to, &from) {
+                        let result =
+                            dijkstra.find_path(start, end, alpha.to_vec(), up_offset, edges);
+                        if let Some(limit) = dijkstra.limited_by(end) {
+                            limited_by.borrow_mut().push(limit);
+                        }
+                        match result {
+                            Some(costs) => costs.1,
+                            None => vec![COST_MAX; dim],
+                        }
+                    },
+                    // to-edges
+                    |node_id| -> Vec<mch::Edge<EdgeId, NodeId>> {
+                        let down_edge_ids =
+                            graph_helper::get_down_edge_ids(node_id, down_offset, down_index);
+                        let mut mch_edges = Vec::new();
+                        for down_edge_id in down_edge_ids {
+                            let edge = &edges[down_edge_id];
+                            mch_edges.push(mch::Edge::new(
+                                edge.id.unwrap(),
+                                edge.from,
+                                edge.to,
+                                edge.cost.clone(),
+                            ))
+                        }
+                        mch_edges
+                    },
+                    // from-edges
+                    |node_id| -> Vec<mch::Edge<EdgeId, NodeId>> {
+                        let up_edge_ids = graph_helper::get_up_edge_ids(node_id, up_offset);
+                        let mut mch_edges = Vec::new();
+                        for up_edge_id in up_edge_ids {
+                            let edge = &edges[up_edge_id];
+                            mch_edges.push(mch::Edge::new(
+                                edge.id.unwrap(),
+                                edge.from,
+                                edge.to,
+                                edge.cost.clone(),
+                            ))
+                        }
+                        mch_edges
+                    },
+                    //dims
+                    dim,
+                ) {
+                    Ok(mch) => mch,
+                    Err(error) => {
+                        panic!("error with mch: '{:?}'", error);
+                    }
+                };
+
+                let mut thread_shortcuts = Vec::with_capacity((dim * minimas.len()) / thread_count);
+                while let Ok((index, to, froms)) = node_recieve.recv() {
+                    // searches of the previous edge do not fit the new targets
+                    dijkstra.borrow_mut().reset_state();
+                    *witness_targets.borrow_mut() = Some((to.clone(), froms.clone()));
+                    for (i, from) in froms.iter().enumerate() {
+                        limited_by.borrow_mut().clear();
+                        let mch_shortcut = match mch.shortcuts(&to, from) {
                             Ok(ok) => ok,
                             Err(err) => panic!("contraction error: '{:?}'", err),
                         };
                         if let Some(shortcut) = mch_shortcut {
-                            thread_shortcuts.push(Edge::shortcut(
-                                shortcut.from,
-                                shortcut.to,
-                                shortcut.cost,
-                                shortcut_id.fetch_add(1, Ordering::SeqCst),
-                                shortcut.replaced_edges,
+                            thread_shortcuts.push((
+                                index + i,
+                                Edge::shortcut(
+                                    shortcut.from,
+                                    shortcut.to,
+                                    shortcut.cost,
+                                    INVALID_EDGE,
+                                    shortcut.replaced_edges,
+                                ),
+                                limited_by.borrow().clone(),
                             ));
                         }
                     }
-
-                    let mut tmp = parallel_shortcuts.lock().unwrap();
-                    tmp.extend(thread_shortcuts);
-                });
-            }
-            for node_id in &minimas {
-                for to_edge_id in graph_helper::get_down_edge_ids(*node_id, down_offset, down_index)
-                {
-                    let edge = &edges[to_edge_id];
-                    let to_edge =
-                        mch::Edge::new(edge.id.unwrap(), edge.from, edge.to, edge.cost.clone());
-                    for from_edge_id in graph_helper::get_up_edge_ids(*node_id, up_offset) {
-                        let edge = &edges[from_edge_id];
-                        let from_edge =
-                            mch::Edge::new(edge.id.unwrap(), edge.from, edge.to, edge.cost.clone());
-                        node_send.send((to_edge.clone(), from_edge)).unwrap();
-                    }
                 }
+
+                let mut tmp = parallel_shortcuts.lock().unwrap();
+                tmp.extend(thread_shortcuts);
+            });
+        }
+        // one message per incoming edge, so its witness searches are shared by all outgoing edges
+        let mut index = 0;
+        for node_id in &minimas {
+            let from_edges: Vec<MchEdge> = graph_helper::get_up_edge_ids(*node_id, up_offset)
+                .into_iter()
+                .map(|from_edge_id| {
+                    let edge = &edges[from_edge_id];
+                    mch::Edge::new(edge.id.unwrap(), edge.from, edge.to, edge.cost.clone())
+                })
+                .collect();
+            for to_edge_id in graph_helper::get_down_edge_ids(*node_id, down_offset, down_index) {
+                let edge = &edges[to_edge_id];
+                let to_edge =
+                    mch::Edge::new(edge.id.unwrap(), edge.from, edge.to, edge.cost.clone());
+                node_send
+                    .send((index, to_edge, from_edges.clone()))
+                    .unwrap();
+                index += from_edges.len();
             }
-            drop(node_send);
-        });
-
-        let mut shortcuts = parallel_shortcuts.into_inner().unwrap();
-
-        // collecting all edges to be removed
-        let mut connected_edges: Vec<EdgeId> = minimas
-            .par_iter()
-            .map(|node| graph_helper::get_all_edge_ids(*node, up_offset, down_offset, down_index))
-            .flatten()
-            .collect();
-
-        // dedup shortcuts; preventing shortcuts in diamond-shapes
-        shortcuts.par_sort_unstable_by(|a, b| {
-            a.from
-                .cmp(&b.from)
-                .then(a.to.cmp(&b.to))
-                .then(a.cost.partial_cmp(&(b.cost)).unwrap())
-        });
-        // only dedup exakt duplicates
-        shortcuts.dedup_by(|a, b| a.from == b.from && a.to == b.to && same_array(&a.cost, &b.cost));
-
-        // update heuristic of neighbors of I with simulated contractions
-        let mut neighbors: Vec<NodeId> = minimas
-            .par_iter()
-            .map(|node| {
-                graph_helper::get_all_neighbours(*node, edges, up_offset, down_offset, down_index)
+        }
+        drop(node_send);
+    });
+
+    // assign ids in sending order instead of finishing order of the threads,
+    // so every run and every resumed run creates the same shortcuts
+    let mut shortcuts = parallel_shortcuts.into_inner().unwrap();
+    shortcuts.sort_unstable_by_key(|(index, _, _)| *index);
+    let first_shortcut_id = state.shortcut_id;
+    let mut limited_by = Vec::with_capacity(shortcuts.len());
+    let mut shortcuts: Vec<Edge> = shortcuts
+        .into_iter()
+        .map(|(_, mut shortcut, shortcut_limited_by)| {
+            shortcut.id = Some(state.shortcut_id);
+            state.shortcut_id += 1;
+            limited_by.push(shortcut_limited_by);
+            shortcut
+        })
+        .collect();
+
+    // collecting all edges to be removed
+    let mut connected_edges: Vec<EdgeId> = minimas
+        .par_iter()
+        .map(|node| graph_helper::get_all_edge_ids(*node, up_offset, down_offset, down_index))
+        .flatten()
+        .collect();
+
+    // dedup shortcuts; preventing shortcuts in diamond-shapes
+    shortcuts.par_sort_unstable_by(|a, b| {
+        a.from
+            .cmp(&b.from)
+            .then(a.to.cmp(&b.to))
+            .then(a.cost.partial_cmp(&(b.cost)).unwrap())
+            .then(a.id.cmp(&b.id))
+    });
+    // only dedup exakt duplicates
+    shortcuts.dedup_by(|a, b| a.from == b.from && a.to == b.to && same_array(&a.cost, &b.cost));
+
+    // shortcuts, whose witness search was cut by a limit, might not be needed
+    let limited_shortcuts = |limit| {
+        shortcuts
+            .iter()
+            .filter(|shortcut| {
+                limited_by[shortcut.id.unwrap() - first_shortcut_id].contains(&limit)
             })
-            .flatten()
-            .collect();
-        for neighbor in &neighbors {
-            deleted_neighbors[*neighbor] += 1;
+            .count()
+    };
+    let hop_limited_shortcuts = limited_shortcuts(WitnessLimit::Hops);
+    let settled_limited_shortcuts = limited_shortcuts(WitnessLimit::SettledNodes);
+
+    // neighbors of I, their heuristics change with the contraction
+    let minima_neighbors: Vec<Vec<NodeId>> = minimas
+        .par_iter()
+        .map(|node| {
+            graph_helper::get_all_neighbours(*node, edges, up_offset, down_offset, down_index)
+        })
+        .collect();
+    for (node, node_neighbors) in minimas.iter().zip(&minima_neighbors) {
+        for neighbor in node_neighbors {
+            state.deleted_neighbors[*neighbor] += 1;
+            state.depths[*neighbor] = state.depths[*neighbor].max(state.depths[*node] + 1);
         }
-        neighbors.par_sort_unstable();
-        neighbors.dedup();
-        ordering::update_neighbor_heuristics(
-            neighbors,
-            level,
-            heuristics,
-            nodes,
-            deleted_neighbors,
+    }
+    let mut neighbors: Vec<NodeId> = minima_neighbors.into_iter().flatten().collect();
+    neighbors.par_sort_unstable();
+    neighbors.dedup();
+
+    // sort in reverse order for removing from bottom up
+    connected_edges.par_sort_unstable_by_key(|&edge| Reverse(edge));
+    // insert E into remaining graph
+    for edge_id in connected_edges.iter() {
+        state.resulting_edges.push(edges.swap_remove(*edge_id));
+    }
+
+    // add new shortcuts to edges
+    let amount_shortcuts = shortcuts.len();
+    edges.par_extend(shortcuts);
+
+    // recalc edge-indices
+    *down_index = offset::generate_offsets(edges, up_offset, down_offset, nodes.len());
+
+    // update heuristic of neighbors of I in the remaining graph
+    ordering::update_neighbor_heuristics(
+        neighbors,
+        state.level,
+        &mut state.heuristics,
+        nodes,
+        &OrderingGraph {
+            edges,
             up_offset,
             down_offset,
-        );
+            down_index,
+            deleted_neighbors: &state.deleted_neighbors,
+            depths: &state.depths,
+        },
+        node_ordering.heuristic.as_ref(),
+        dim,
+    );
+
+    // move I to their rank
+    for node in &minimas {
+        nodes[*node].rank = state.rank;
+        state.remaining_nodes.remove(node);
+    }
+    state.contracted_nodes_amount += minimas.len();
+
+    let report = RankReport {
+        rank: state.rank,
+        level: state.level,
+        remaining_nodes: state.remaining_nodes.len(),
+        independent_set: minimas.len(),
+        edges: edges.len(),
+        shortcuts: amount_shortcuts,
+        hop_limited_shortcuts,
+        settled_limited_shortcuts,
+        removed_edges: connected_edges.len(),
+        resulting_edges: state.resulting_edges.len(),
+        seconds: rank_time.elapsed().as_secs_f64(),
+    };
+    state.ranks.push(report);
+    state.rank += 1;
+}
 
-        // sort in reverse order for removing from bottom up
-        connected_edges.par_sort_unstable_by_key(|&edge| Reverse(edge));
-        // insert E into remaining graph
-        for edge_id in connected_edges.iter() {
-            resulting_edges.push(edges.swap_remove(*edge_id));
-        }
+fn print_rank(report: &RankReport) {
+    println!(
+        "rank {:?}  \tremaining_nodes {:?} \tindependent_set {:?} \tedges {:?} \tshortcuts {:?}     \tremoving_edges {:?} \tresulting_edges {:?}",
+        report.rank,
+        report.remaining_nodes,
+        report.independent_set,
+        report.edges,
+        report.shortcuts,
+        report.removed_edges,
+        report.resulting_edges
+    );
+}
 
-        // add new shortcuts to edges
-        let amount_shortcuts = shortcuts.len();
-        edges.par_extend(shortcuts);
+/// local graph of a cell after its contraction
+struct ContractedCell {
+    /// global id of every local node
+    global_ids: Vec<NodeId>,
+    state: ContractionState,
+    nodes: Vec<Node>,
+    /// remaining edges between the nodes of higher levels
+    edges: Vec<Edge>,
+}
 
-        // recalc edge-indices
-        *down_index = offset::generate_offsets(edges, up_offset, down_offset, nodes.len());
+/// contract the remaining nodes of the level cell by cell in parallel.
+/// nodes of a level only have neighbors in their own cell of that level, so no shortcut connects
+/// two cells. witnesses leaving the cell are not found, which can add shortcuts.
+/// the k-th independent sets of all cells form the k-th rank of the level.
+/// the cells stop in proportion to their size, once more than `contraction_stop` of all nodes
+/// are contracted
+#[allow(clippy::too_many_arguments)]
+fn contract_cells(
+    state: &mut ContractionState,
+    contraction_stop: f64,
+    node_ordering: &NodeOrdering,
+    witness_limits: WitnessLimits,
+    nodes: &mut [Node],
+    edges: &mut Vec<Edge>,
+    up_offset: &mut Vec<EdgeId>,
+    down_offset: &mut Vec<EdgeId>,
+    down_index: &mut Vec<EdgeId>,
+    mlp_levels: &[usize],
+    dim: usize,
+) {
+    let level = state.level;
+    let cell_time = Instant::now();
+
+    // nodes of the remaining graph grouped by their cell, with their index inside of it
+    let cells_amount = mlp_levels[level..].iter().product::<usize>();
+    let cell_of =
+        |node_id: NodeId| mlp_helper::get_partition_id_on_level(node_id, level, nodes, mlp_levels);
+    let mut cell_nodes: Vec<Vec<NodeId>> = vec![Vec::new(); cells_amount];
+    let mut local_ids = vec![INVALID_NODE; nodes.len()];
+    for (node_id, node) in nodes.iter().enumerate() {
+        if node.level >= level {
+            let cell = &mut cell_nodes[cell_of(node_id)];
+            local_ids[node_id] = cell.len();
+            cell.push(node_id);
+        }
+    }
+    let amount_cell_nodes = cell_nodes.iter().map(|cell| cell.len()).sum::<usize>();
+    let cell_stop = contraction_stop * nodes.len() as f64 / amount_cell_nodes as f64;
+    let has_remaining: Vec<bool> = cell_nodes
+        .iter()
+        .map(|cell| {
+            cell.iter()
+                .any(|node_id| state.remaining_nodes.contains(node_id))
+        })
+        .collect();
+
+    // edges inside of a cell with remaining nodes go into its local graph, all others stay
+    let mut cell_edges: Vec<Vec<Edge>> = vec![Vec::new(); cells_amount];
+    let mut kept_edges = Vec::new();
+    for edge in edges.drain(..) {
+        let cell = cell_of(edge.from);
+        if has_remaining[cell] && cell == cell_of(edge.to) {
+            cell_edges[cell].push(Edge {
+                from: local_ids[edge.from],
+                to: local_ids[edge.to],
+                ..edge
+            });
+        } else {
+            kept_edges.push(edge);
+        }
+    }
 
-        // move I to their rank
-        for node in &minimas {
-            nodes[*node].rank = *rank;
-            remaining_nodes.remove(node);
+    // every cell numbers its shortcuts from the same id, they are shifted while merging
+    let first_shortcut_id = state.shortcut_id;
+    let deleted_neighbors = &state.deleted_neighbors;
+    let depths = &state.depths;
+    let remaining_nodes = &state.remaining_nodes;
+    let cells: Vec<ContractedCell> = cell_nodes
+        .into_par_iter()
+        .zip(cell_edges)
+        .zip(has_remaining)
+        .filter(|(_, has_remaining)| *has_remaining)
+        .map(|((global_ids, mut local_edges), _)| {
+            let mut local_nodes: Vec<Node> = global_ids
+                .iter()
+                .map(|node_id| nodes[*node_id].clone())
+                .collect();
+            let mut local_up_offset = Vec::<EdgeId>::new();
+            let mut local_down_offset = Vec::<EdgeId>::new();
+            let mut local_down_index = offset::generate_offsets(
+                &mut local_edges,
+                &mut local_up_offset,
+                &mut local_down_offset,
+                local_nodes.len(),
+            );
+            let mut local_state = ContractionState {
+                level,
+                level_started: true,
+                rank: 0,
+                remaining_nodes: global_ids
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, node_id)| remaining_nodes.contains(node_id))
+                    .map(|(local_id, _)| local_id)
+                    .collect(),
+                contracted_nodes_amount: 0,
+                heuristics: Vec::new(),
+                deleted_neighbors: global_ids
+                    .iter()
+                    .map(|node_id| deleted_neighbors[*node_id])
+                    .collect(),
+                depths: global_ids.iter().map(|node_id| depths[*node_id]).collect(),
+                shortcut_id: first_shortcut_id,
+                resulting_edges: Vec::new(),
+                ranks: Vec::new(),
+            };
+            local_state.heuristics = ordering::calculate_heuristics(
+                level,
+                &local_nodes,
+                &OrderingGraph {
+                    edges: &local_edges,
+                    up_offset: &local_up_offset,
+                    down_offset: &local_down_offset,
+                    down_index: &local_down_index,
+                    deleted_neighbors: &local_state.deleted_neighbors,
+                    depths: &local_state.depths,
+                },
+                node_ordering.heuristic.as_ref(),
+                dim,
+            );
+            let mut independent_set_flags = ValidFlag::new(local_nodes.len());
+            while !(local_state.remaining_nodes.is_empty()
+                || (local_state.contracted_nodes_amount as f64 / local_nodes.len() as f64)
+                    > cell_stop)
+            {
+                // the cells are the parallelism, a single thread per cell avoids contention
+                contract_independent_set(
+                    &mut local_state,
+                    node_ordering,
+                    witness_limits,
+                    &mut independent_set_flags,
+                    &mut local_nodes,
+                    &mut local_edges,
+                    &mut local_up_offset,
+                    &mut local_down_offset,
+                    &mut local_down_index,
+                    dim,
+                    1,
+                );
+            }
+            ContractedCell {
+                global_ids,
+                state: local_state,
+                nodes: local_nodes,
+                edges: local_edges,
+            }
+        })
+        .collect();
+
+    // merge the cells in their order, so the result does not depend on the threads
+    let first_rank = state.rank;
+    let contracted_cells = cells.len();
+    let amount_ranks = cells.iter().map(|cell| cell.state.rank).max().unwrap_or(0);
+    let resulting_edges_before = state.resulting_edges.len();
+    let amount_kept_edges = kept_edges.len();
+    let mut ranks: Vec<RankReport> = (0..amount_ranks)
+        .map(|rank| RankReport {
+            rank: first_rank + rank,
+            level,
+            remaining_nodes: 0,
+            independent_set: 0,
+            edges: amount_kept_edges,
+            shortcuts: 0,
+            hop_limited_shortcuts: 0,
+            settled_limited_shortcuts: 0,
+            removed_edges: 0,
+            resulting_edges: resulting_edges_before,
+            seconds: 0.0,
+        })
+        .collect();
+    let mut shortcut_offset = 0;
+    *edges = kept_edges;
+    for ContractedCell {
+        global_ids,
+        state: local_state,
+        nodes: local_nodes,
+        edges: local_edges,
+    } in cells
+    {
+        let global_edge = |mut edge: Edge| {
+            let global_id = |id: EdgeId| {
+                if id >= first_shortcut_id {
+                    id + shortcut_offset
+                } else {
+                    id
+                }
+            };
+            edge.from = global_ids[edge.from];
+            edge.to = global_ids[edge.to];
+            edge.id = edge.id.map(global_id);
+            edge.contracted_edges = edge
+                .contracted_edges
+                .map(|(prev, next)| (global_id(prev), global_id(next)));
+            edge
+        };
+        edges.extend(local_edges.into_iter().map(global_edge));
+        state
+            .resulting_edges
+            .extend(local_state.resulting_edges.into_iter().map(global_edge));
+
+        for (local_id, node_id) in global_ids.iter().enumerate() {
+            // nodes left by the contraction stop remain for the whole level
+            if !local_state.remaining_nodes.contains(&local_id)
+                && state.remaining_nodes.remove(node_id)
+            {
+                nodes[*node_id].rank = first_rank + local_nodes[local_id].rank;
+            }
+            state.deleted_neighbors[*node_id] = local_state.deleted_neighbors[local_id];
+            state.depths[*node_id] = local_state.depths[local_id];
+        }
+        state.contracted_nodes_amount += local_state.contracted_nodes_amount;
+
+        // a finished cell keeps its last state in the following ranks
+        let last = local_state.ranks.last().unwrap();
+        for (rank, report) in ranks.iter_mut().enumerate() {
+            match local_state.ranks.get(rank) {
+                Some(local_report) => {
+                    report.remaining_nodes += local_report.remaining_nodes;
+                    report.independent_set += local_report.independent_set;
+                    report.edges += local_report.edges;
+                    report.shortcuts += local_report.shortcuts;
+                    report.hop_limited_shortcuts += local_report.hop_limited_shortcuts;
+                    report.settled_limited_shortcuts += local_report.settled_limited_shortcuts;
+                    report.removed_edges += local_report.removed_edges;
+                    report.resulting_edges += local_report.resulting_edges;
+                    report.seconds = report.seconds.max(local_report.seconds);
+                }
+                None => {
+                    report.edges += last.edges;
+                    report.resulting_edges += last.resulting_edges;
+                }
+            }
         }
-        contracted_nodes_amount += minimas.len();
-
-        println!(
-            "rank {:?}  \tremaining_nodes {:?} \tindependent_set {:?} \tedges {:?} \tshortcuts {:?}     \tremoving_edges {:?} \tresulting_edges {:?}",
-            rank,
-            remaining_nodes.len(),
-            minimas.len(),
-            edges.len(),
-            amount_shortcuts,
-            connected_edges.len(),
-            resulting_edges.len()
-        );
-        *rank += 1;
+        shortcut_offset += local_state.shortcut_id - first_shortcut_id;
     }
+    state.shortcut_id += shortcut_offset;
+    state.rank += amount_ranks;
+    state.ranks.extend(ranks);
+
+    *down_index = offset::generate_offsets(edges, up_offset, down_offset, nodes.len());
+    println!(
+        "contracted {} cells of level {} in {:?}",
+        contracted_cells,
+        level,
+        cell_time.elapsed()
+    );
 }
 
+/// contract level after level, `save` is called after every level and every `every_ranks` ranks.
+/// with a checkpoint of an interrupted run the contraction continues where it stopped.
+/// returns the statistics of every rank
+#[allow(clippy::too_many_arguments)]
 pub fn prp_contraction(
     nodes: &mut [Node],
     edges: &mut Vec<Edge>,
@@ -332,66 +718,172 @@ pub fn prp_contraction(
     down_index: &mut Vec<EdgeId>,
     mlp_levels: &[usize],
     contraction_stop: f64,
-) {
+    node_ordering: &NodeOrdering,
+    witness_limits: WitnessLimits,
+    cell_contraction: bool,
+    graph_hash: u64,
+    every_ranks: Option<usize>,
+    resume: Option<Checkpoint>,
+    save: &mut dyn FnMut(&CheckpointRef) -> bincode::Result<()>,
+) -> bincode::Result<Vec<RankReport>> {
+    // the remaining graph can run out of edges
+    let dim = edges[0].cost.len();
     let mut independent_set_flags = ValidFlag::new(nodes.len());
 
-    let mut deleted_neighbors = vec![0; nodes.len()];
-
-    let mut rank: Rank = 0;
-
-    // edge-ids
-    let shortcut_id = AtomicUsize::new(edges.len());
-    // make edges have indices
-    edges
-        .par_iter_mut()
-        .enumerate()
-        .for_each(|(i, x)| x.id = Some(i));
-
-    let contracted_nodes_amount = 0;
-
-    let mut resulting_edges = Vec::<Edge>::with_capacity(edges.len() * mlp_levels.len());
+    let mut state = match resume {
+        Some(checkpoint) => checkpoint.restore(nodes, edges, up_offset, down_offset, down_index),
+        None => {
+            // make edges have indices
+            edges
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(i, x)| x.id = Some(i));
+            ContractionState {
+                level: 0,
+                level_started: false,
+                rank: 0,
+                remaining_nodes: BTreeSet::new(),
+                contracted_nodes_amount: 0,
+                heuristics: Vec::new(),
+                deleted_neighbors: vec![0; nodes.len()],
+                depths: vec![0; nodes.len()],
+                // edge-ids
+                shortcut_id: edges.len(),
+                resulting_edges: Vec::with_capacity(edges.len() * mlp_levels.len()),
+                ranks: Vec::new(),
+            }
+        }
+    };
+
+    while state.level < mlp_levels.len() {
+        let level = state.level;
+        let first_rank = state.ranks.len();
+        // a single cell is contracted as a whole, it may stop early
+        let cell_level = cell_contraction && mlp_levels[level..].iter().product::<usize>() > 1;
+        if !state.level_started {
+            state.remaining_nodes = nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.level == level)
+                .map(|(node_id, _)| node_id)
+                .collect();
+
+            // assign core-edge
+            for edge in edges.iter_mut() {
+                edge.level = Some(level);
+            }
 
-    for level in 0..mlp_levels.len() {
-        let mut remaining_nodes = BTreeSet::new();
-        for (node_id, node) in nodes.iter().enumerate() {
-            if level == node.level {
-                remaining_nodes.insert(node_id);
+            // cells set up their own heuristics
+            if !cell_level {
+                state.heuristics = ordering::calculate_heuristics(
+                    level,
+                    nodes,
+                    &OrderingGraph {
+                        edges,
+                        up_offset,
+                        down_offset,
+                        down_index,
+                        deleted_neighbors: &state.deleted_neighbors,
+                        depths: &state.depths,
+                    },
+                    node_ordering.heuristic.as_ref(),
+                    dim,
+                );
             }
+            state.contracted_nodes_amount = 0;
+            state.level_started = true;
         }
 
-        // assign core-edge
-        for edge in edges.iter_mut() {
-            edge.level = Some(level);
+        if cell_level {
+            contract_cells(
+                &mut state,
+                contraction_stop,
+                node_ordering,
+                witness_limits,
+                nodes,
+                edges,
+                up_offset,
+                down_offset,
+                down_index,
+                mlp_levels,
+                dim,
+            );
+            state.ranks[first_rank..].iter().for_each(print_rank);
+            // the remaining nodes of stopped cells are contracted as a whole
+            if !state.remaining_nodes.is_empty() {
+                state.heuristics = ordering::calculate_heuristics(
+                    level,
+                    nodes,
+                    &OrderingGraph {
+                        edges,
+                        up_offset,
+                        down_offset,
+                        down_index,
+                        deleted_neighbors: &state.deleted_neighbors,
+                        depths: &state.depths,
+                    },
+                    node_ordering.heuristic.as_ref(),
+                    dim,
+                );
+            }
+        }
+        while !(state.remaining_nodes.is_empty()
+            || (state.contracted_nodes_amount as f64 / nodes.len() as f64) > contraction_stop)
+        {
+            contract_independent_set(
+                &mut state,
+                node_ordering,
+                witness_limits,
+                &mut independent_set_flags,
+                nodes,
+                edges,
+                up_offset,
+                down_offset,
+                down_index,
+                dim,
+                num_cpus::get(),
+            );
+            print_rank(state.ranks.last().unwrap());
+            if every_ranks.is_some_and(|every| state.rank % every == 0) {
+                save(&CheckpointRef::new(
+                    &state,
+                    graph_hash,
+                    nodes,
+                    edges,
+                    up_offset,
+                    down_offset,
+                    down_index,
+                    mlp_levels,
+                    contraction_stop,
+                    node_ordering,
+                    witness_limits,
+                    cell_contraction,
+                ))?;
+            }
         }
 
-        let mut heuristics = ordering::calculate_heuristics(
-            level,
-            nodes,
-            &deleted_neighbors,
-            up_offset,
-            down_offset,
-        );
-        level_contraction(
-            level,
-            &mut remaining_nodes,
-            &mut independent_set_flags,
-            &mut heuristics,
-            &mut deleted_neighbors,
-            &shortcut_id,
-            &mut resulting_edges,
-            &mut rank,
+        state.level += 1;
+        state.level_started = false;
+        save(&CheckpointRef::new(
+            &state,
+            graph_hash,
             nodes,
             edges,
             up_offset,
             down_offset,
             down_index,
-            contracted_nodes_amount,
+            mlp_levels,
             contraction_stop,
-        );
+            node_ordering,
+            witness_limits,
+            cell_contraction,
+        ))?;
     }
+    let resulting_edges = state.resulting_edges;
+    let ranks = state.ranks;
 
     // testing uniqueness of ids
-    let unique_set: BTreeSet<usize> = edges.iter().cloned().map(|e| e.id.unwrap()).collect();
+    let unique_set: BTreeSet<usize> = edges.iter().map(|e| e.id.unwrap()).collect();
     assert_eq!(unique_set.len(), edges.len());
 
     // assign each top edge to top level
@@ -426,6 +918,7 @@ pub fn prp_contraction(
 
     // revert the edge-ids back to usual ids
     revert_indices(edges);
+    Ok(ranks)
 }
 
 #[test]
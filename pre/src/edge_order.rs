@@ -0,0 +1,135 @@
+use super::*;
+use std::cmp::Reverse;
+
+/// edge orders of every query type, so the query does not have to sort edges while loading
+pub fn generate_edge_orders(
+    nodes: &[Node],
+    edges: &[Edge],
+    up_offset: &[EdgeId],
+    down_offset: &[EdgeId],
+    down_index: &[EdgeId],
+) -> EdgeOrders {
+    let order = |sort_up: &dyn Fn(&mut [EdgeId]), sort_down: &dyn Fn(&mut [EdgeId])| {
+        let mut up: Vec<EdgeId> = (0..edges.len()).collect();
+        let mut down = down_index.to_vec();
+        for node in 0..nodes.len() {
+            sort_up(&mut up[up_offset[node]..up_offset[node + 1]]);
+            sort_down(&mut down[down_offset[node]..down_offset[node + 1]]);
+        }
+        EdgeOrder { up, down }
+    };
+
+    // original edges first, shortcuts are skipped
+    let normal = order(
+        &|ids| ids.sort_unstable_by_key(|id| edges[*id].contracted_edges),
+        &|ids| ids.sort_unstable_by_key(|id| edges[*id].contracted_edges),
+    );
+
+    // sort by rank
+    let pch = order(
+        &|ids| ids.sort_unstable_by_key(|id| Reverse(nodes[edges[*id].to].rank)),
+        &|ids| ids.sort_unstable_by_key(|id| Reverse(nodes[edges[*id].from].rank)),
+    );
+
+    // sort by level
+    let pcrp = order(
+        &|ids| ids.sort_unstable_by_key(|id| Reverse(edges[*id].level)),
+        &|ids| ids.sort_unstable_by_key(|id| Reverse(edges[*id].level)),
+    );
+
+    let minimum_rank_of_layer_one = edges
+        .iter()
+        .filter(|edge| edge.level == Some(1))
+        .map(|edge| nodes[edge.from].rank)
+        .min()
+        .unwrap_or(INVALID_RANK);
+    let prp = order(
+        &|ids| {
+            sort_prp(ids, minimum_rank_of_layer_one, &|id| {
+                (
+                    edges[id].level,
+                    nodes[edges[id].from].rank,
+                    nodes[edges[id].to].rank,
+                )
+            })
+        },
+        &|ids| {
+            sort_prp(ids, minimum_rank_of_layer_one, &|id| {
+                (
+                    edges[id].level,
+                    nodes[edges[id].to].rank,
+                    nodes[edges[id].from].rank,
+                )
+            })
+        },
+    );
+
+    EdgeOrders {
+        normal,
+        pch,
+        pcrp,
+        prp,
+    }
+}
+
+/// sort by level then by rank, the pch edges in front of layer one by the rank of their neighbor.
+/// `ranks` returns the level of the edge, the rank of its own node and the rank of the neighbor
+fn sort_prp(
+    ids: &mut [EdgeId],
+    minimum_rank_of_layer_one: Rank,
+    ranks: &dyn Fn(EdgeId) -> (Option<Level>, Rank, Rank),
+) {
+    // sort by level then by rank but in wrong order
+    ids.sort_unstable_by(|a, b| {
+        let (level_a, rank_a, _) = ranks(*a);
+        let (level_b, rank_b, _) = ranks(*b);
+        level_a.cmp(&level_b).reverse().then(rank_a.cmp(&rank_b))
+    });
+    // find index of pch edges
+    let pch_level_one_index = ids
+        .iter()
+        .position(|id| {
+            let (level, rank, _) = ranks(*id);
+            level.is_none() && rank > minimum_rank_of_layer_one
+        })
+        .unwrap_or(ids.len());
+
+    // sort pch edges by rank of the neighbor
+    ids[..pch_level_one_index].sort_unstable_by_key(|id| Reverse(ranks(*id).2));
+}
+
+#[test]
+fn edge_orders() {
+    let node = |rank| Node {
+        latitude: 0.0,
+        longitude: 0.0,
+        rank,
+        partition: 0,
+        level: 0,
+        old_id: None,
+    };
+    let nodes = vec![node(0), node(2), node(1)];
+    // 0 -> 1 -> 2 with a shortcut 0 -> 2
+    let mut edges = vec![
+        Edge::new(0, 1, vec![1.0]),
+        Edge::new(0, 2, vec![2.0]),
+        Edge::new(1, 2, vec![1.0]),
+    ];
+    let mut up_offset = Vec::<EdgeId>::new();
+    let mut down_offset = Vec::<EdgeId>::new();
+    let down_index =
+        offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, nodes.len());
+    edges[1].contracted_edges = Some((0, 2));
+    edges[1].level = Some(1);
+
+    let orders = generate_edge_orders(&nodes, &edges, &up_offset, &down_offset, &down_index);
+
+    assert_eq!(orders.normal.up, vec![0, 1, 2]);
+    assert_eq!(orders.normal.down[1..], [2, 1]);
+    // higher ranked targets first
+    assert_eq!(orders.pch.up, vec![0, 1, 2]);
+    assert_eq!(orders.pch.down[1..], [2, 1]);
+    // higher levels first
+    assert_eq!(orders.pcrp.up, vec![1, 0, 2]);
+    assert_eq!(orders.pcrp.down[1..], [1, 2]);
+}
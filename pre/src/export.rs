@@ -1,9 +1,11 @@
 use super::*;
 use bincode::serialize_into;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
 
 pub fn write_to_disk(filename: &str, result: &BinFile) -> Result<(), Box<bincode::ErrorKind>> {
     let mut writer = BufWriter::new(File::create(filename).unwrap());
+    writer.write_all(&BIN_FILE_MAGIC)?;
+    serialize_into(&mut writer, &BIN_FILE_VERSION)?;
     serialize_into(&mut writer, &result)
 }
@@ -1,4 +1,3 @@
-use crate::constants::*;
 use crate::structs::*;
 
 pub fn read_file(
@@ -51,14 +50,7 @@ pub fn read_file(
     for _ in 0..node_amount {
         if let Some(line) = reader.read_line(&mut buffer) {
             let v: Vec<&str> = line?.trim().split(' ').collect();
-            nodes.push(Node {
-                latitude: v[2].parse().unwrap(),
-                longitude: v[3].parse().unwrap(),
-                rank: INVALID_RANK,
-                partition: INVALID_PARTITION,
-                level: INVALID_LEVEL,
-                old_id: None,
-            });
+            nodes.push(Node::new(v[2].parse().unwrap(), v[3].parse().unwrap()));
         }
     }
 
@@ -0,0 +1,276 @@
+use super::*;
+use ndijkstra::NDijkstra;
+
+use std::fmt;
+
+/// order of contraction, nodes with lower priorities are contracted first
+pub type Priority = isize;
+
+/// remaining graph of the level, that is contracted
+pub struct OrderingGraph<'a> {
+    pub edges: &'a [Edge],
+    pub up_offset: &'a [EdgeId],
+    pub down_offset: &'a [EdgeId],
+    pub down_index: &'a [EdgeId],
+    /// amount of contracted neighbors of every node
+    pub deleted_neighbors: &'a [usize],
+    /// one more than the deepest contracted neighbor, zero without contracted neighbors
+    pub depths: &'a [usize],
+}
+
+/// dijkstra of a single thread, reused for every node it evaluates
+pub struct WitnessSearch {
+    dijkstra: NDijkstra,
+    alpha: Vec<f64>,
+}
+
+impl WitnessSearch {
+    pub fn new(amount_nodes: usize, dim: usize) -> Self {
+        WitnessSearch {
+            dijkstra: NDijkstra::new(amount_nodes, dim),
+            alpha: mch::equal_weights(dim),
+        }
+    }
+
+    /// the graph changes between ranks, so nothing is kept from earlier searches
+    pub fn reset(&mut self) {
+        self.dijkstra.reset_state();
+    }
+
+    /// edge-ids of the shortest path with all metrics weighted equally
+    pub fn shortest_path(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        graph: &OrderingGraph,
+    ) -> Option<Vec<EdgeId>> {
+        self.dijkstra
+            .find_path(from, to, self.alpha.clone(), graph.up_offset, graph.edges)
+            .map(|(path, _)| path)
+    }
+}
+
+/// priority of a node for the contraction, evaluated in parallel for all nodes of a level
+/// and again for the neighbors of every contracted independent set
+pub trait Heuristic: Send + Sync {
+    fn priority(
+        &self,
+        node: NodeId,
+        graph: &OrderingGraph,
+        witness: &mut WitnessSearch,
+    ) -> Priority;
+
+    /// name used on the command line
+    fn name(&self) -> String;
+}
+
+impl fmt::Debug for dyn Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// in-degree times out-degree, the amount of shortcuts a contraction could create at most
+pub struct Degree;
+
+impl Heuristic for Degree {
+    fn priority(&self, node: NodeId, graph: &OrderingGraph, _: &mut WitnessSearch) -> Priority {
+        ((graph.up_offset[node + 1] - graph.up_offset[node])
+            * (graph.down_offset[node + 1] - graph.down_offset[node])) as Priority
+    }
+
+    fn name(&self) -> String {
+        "degree".to_string()
+    }
+}
+
+/// created shortcuts minus removed edges of a simulated contraction.
+/// a shortcut is needed, if the shortest path with equally weighted metrics leads over the node
+pub struct EdgeDifference;
+
+impl Heuristic for EdgeDifference {
+    fn priority(
+        &self,
+        node: NodeId,
+        graph: &OrderingGraph,
+        witness: &mut WitnessSearch,
+    ) -> Priority {
+        witness.reset();
+        let down_edges = graph_helper::get_down_edge_ids(node, graph.down_offset, graph.down_index);
+        let up_edges = graph_helper::get_up_edge_ids(node, graph.up_offset);
+        let mut shortcuts = 0;
+        for down_edge in &down_edges {
+            let from = graph.edges[*down_edge].from;
+            for up_edge in &up_edges {
+                let to = graph.edges[*up_edge].to;
+                if from == to || from == node || to == node {
+                    continue;
+                }
+                let via_node = match witness.shortest_path(from, to, graph) {
+                    Some(path) => path.iter().any(|edge| graph.edges[*edge].to == node),
+                    None => true,
+                };
+                if via_node {
+                    shortcuts += 1;
+                }
+            }
+        }
+        shortcuts as Priority - (down_edges.len() + up_edges.len()) as Priority
+    }
+
+    fn name(&self) -> String {
+        "edge-difference".to_string()
+    }
+}
+
+/// amount of contracted neighbors, spreads the contraction evenly over the graph
+pub struct DeletedNeighbors;
+
+impl Heuristic for DeletedNeighbors {
+    fn priority(&self, node: NodeId, graph: &OrderingGraph, _: &mut WitnessSearch) -> Priority {
+        graph.deleted_neighbors[node] as Priority
+    }
+
+    fn name(&self) -> String {
+        "deleted-neighbors".to_string()
+    }
+}
+
+/// depth of the search space below the node, keeps the upward searches of queries short
+pub struct SearchSpaceDepth;
+
+impl Heuristic for SearchSpaceDepth {
+    fn priority(&self, node: NodeId, graph: &OrderingGraph, _: &mut WitnessSearch) -> Priority {
+        graph.depths[node] as Priority
+    }
+
+    fn name(&self) -> String {
+        "depth".to_string()
+    }
+}
+
+/// sum of weighted heuristics, rounded to the nearest priority
+pub struct Weighted(pub Vec<(f64, Box<dyn Heuristic>)>);
+
+impl Heuristic for Weighted {
+    fn priority(
+        &self,
+        node: NodeId,
+        graph: &OrderingGraph,
+        witness: &mut WitnessSearch,
+    ) -> Priority {
+        self.0
+            .iter()
+            .fold(0.0, |sum, (weight, heuristic)| {
+                sum + weight * heuristic.priority(node, graph, witness) as f64
+            })
+            .round() as Priority
+    }
+
+    fn name(&self) -> String {
+        self.0
+            .iter()
+            .map(|(weight, heuristic)| format!("{}={}", heuristic.name(), weight))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// a single heuristic like `edge-difference` or a weighted combination
+/// like `edge-difference=2,deleted-neighbors=1,depth=1`
+pub fn parse_heuristic(value: &str) -> Result<Box<dyn Heuristic>, String> {
+    let mut parts = Vec::new();
+    for part in value.split(',') {
+        let (name, weight) = match part.split_once('=') {
+            Some((name, weight)) => match weight.trim().parse::<f64>() {
+                Ok(weight) if weight.is_finite() => (name, Some(weight)),
+                _ => return Err(format!("invalid weight '{}' of '{}'", weight, name)),
+            },
+            None => (part, None),
+        };
+        let heuristic: Box<dyn Heuristic> = match name.trim() {
+            "degree" => Box::new(Degree),
+            "edge-difference" => Box::new(EdgeDifference),
+            "deleted-neighbors" => Box::new(DeletedNeighbors),
+            "depth" => Box::new(SearchSpaceDepth),
+            _ => {
+                return Err(format!(
+                    "unknown heuristic '{}', expected degree, edge-difference, deleted-neighbors or depth",
+                    name
+                ))
+            }
+        };
+        parts.push((weight, heuristic));
+    }
+    if parts.len() == 1 && parts[0].0.is_none() {
+        return Ok(parts.pop().unwrap().1);
+    }
+    Ok(Box::new(Weighted(
+        parts
+            .into_iter()
+            .map(|(weight, heuristic)| (weight.unwrap_or(1.0), heuristic))
+            .collect(),
+    )))
+}
+
+/// how the independent sets of the contraction are chosen
+#[derive(Debug)]
+pub struct NodeOrdering {
+    pub heuristic: Box<dyn Heuristic>,
+    /// only the lowest 1/`candidates` of the remaining nodes can be contracted in the next rank
+    pub candidates: usize,
+}
+
+impl Default for NodeOrdering {
+    fn default() -> Self {
+        NodeOrdering {
+            heuristic: Box::new(Degree),
+            candidates: 10,
+        }
+    }
+}
+
+#[test]
+fn heuristics() {
+    // 0 -> 1 -> 2 and a direct edge 0 -> 2, that is longer than the way over 1
+    //      1 -> 3 -> 2 is longer than 1 -> 2
+    let amount_nodes = 4;
+    let mut edges = vec![
+        Edge::new(0, 1, vec![1.0]),
+        Edge::new(1, 2, vec![0.9]),
+        Edge::new(0, 2, vec![5.0]),
+        Edge::new(1, 3, vec![0.5]),
+        Edge::new(3, 2, vec![0.5]),
+    ];
+    let mut up_offset = Vec::<EdgeId>::new();
+    let mut down_offset = Vec::<EdgeId>::new();
+    let down_index =
+        offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, amount_nodes);
+    let deleted_neighbors = vec![0, 2, 1, 0];
+    let depths = vec![1, 0, 3, 0];
+    let graph = OrderingGraph {
+        edges: &edges,
+        up_offset: &up_offset,
+        down_offset: &down_offset,
+        down_index: &down_index,
+        deleted_neighbors: &deleted_neighbors,
+        depths: &depths,
+    };
+    let mut witness = WitnessSearch::new(amount_nodes, 1);
+
+    assert_eq!(Degree.priority(1, &graph, &mut witness), 2);
+    // 0 -> 2 has no witness besides 1, 0 -> 3 has none at all
+    assert_eq!(EdgeDifference.priority(1, &graph, &mut witness), 2 - 3);
+    // 1 -> 2 is a witness for the way over 3
+    assert_eq!(EdgeDifference.priority(3, &graph, &mut witness), 0 - 2);
+    assert_eq!(DeletedNeighbors.priority(1, &graph, &mut witness), 2);
+    assert_eq!(SearchSpaceDepth.priority(2, &graph, &mut witness), 3);
+
+    let weighted = parse_heuristic("edge-difference=2, deleted-neighbors").unwrap();
+    assert_eq!(weighted.name(), "edge-difference=2,deleted-neighbors=1");
+    // 2 * (2 - 3) + 2
+    assert_eq!(weighted.priority(1, &graph, &mut witness), 0);
+    assert_eq!(parse_heuristic("depth").unwrap().name(), "depth");
+    assert!(parse_heuristic("degree=x").is_err());
+    assert!(parse_heuristic("random").is_err());
+}
@@ -0,0 +1,28 @@
+mod checkpoint;
+mod components;
+pub mod constants;
+mod contraction;
+mod edge_order;
+pub mod export;
+pub mod fmi_import;
+mod graph_helper;
+mod grid;
+pub mod heuristic;
+mod min_heap;
+mod mlp_helper;
+pub mod mlp_import;
+mod ndijkstra;
+mod offset;
+mod ordering;
+mod pipeline;
+pub mod report;
+pub mod structs;
+pub mod upgrade;
+mod valid_flag;
+
+pub use constants::*;
+pub use ndijkstra::WitnessLimits;
+pub use pipeline::*;
+pub use structs::*;
+
+use rayon::prelude::*;
@@ -3,30 +3,69 @@
 use super::*;
 use mch::{add_edge_costs, costs_by_alpha, equal_weights, same_array};
 use min_heap::*;
+use serde::{Deserialize, Serialize};
 use std::collections::BinaryHeap;
 use valid_flag::*;
 
+/// limits of witness searches, a witness missed because of a limit leads to a shortcut,
+/// that would not be needed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WitnessLimits {
+    /// edges of a witness at most
+    pub max_hops: Option<usize>,
+    /// settled nodes of a single search at most
+    pub max_settled_nodes: Option<usize>,
+    /// stop searching beyond the cost of the paths over the contracted node.
+    /// this never misses a witness
+    pub cost_bound: bool,
+}
+
+/// why a search did not find a target or might have missed a shorter path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WitnessLimit {
+    Hops,
+    SettledNodes,
+    Cost,
+}
+
 #[derive(Clone)]
 pub struct NDijkstra {
     dist: Vec<(Cost, Option<NodeId>)>,
+    hops: Vec<usize>,
     heap: BinaryHeap<MinHeapItem>,
     visited: ValidFlag,
     last_from: NodeId,
     last_pref: Vec<Cost>,
+    limits: WitnessLimits,
+    max_cost: Cost,
+    settled_nodes: usize,
+    stopped: Option<WitnessLimit>,
+    pruned_hops: bool,
 }
 
 impl NDijkstra {
     /// general constructor
     pub fn new(amount_nodes: usize, dim: usize) -> Self {
+        NDijkstra::with_limits(amount_nodes, dim, WitnessLimits::default())
+    }
+
+    /// searches stop at the given limits
+    pub fn with_limits(amount_nodes: usize, dim: usize, limits: WitnessLimits) -> Self {
         let dist = vec![(COST_MAX, None); amount_nodes];
         let heap = BinaryHeap::new();
         let visited = ValidFlag::new(amount_nodes);
         NDijkstra {
             dist,
+            hops: vec![0; amount_nodes],
             heap,
             visited,
             last_from: INVALID_NODE,
             last_pref: equal_weights(dim),
+            limits,
+            max_cost: COST_MAX,
+            settled_nodes: 0,
+            stopped: None,
+            pruned_hops: false,
         }
     }
 
@@ -35,6 +74,17 @@ impl NDijkstra {
         self.last_from = INVALID_NODE;
         self.heap.clear();
         self.visited.invalidate_all();
+        self.settled_nodes = 0;
+        self.stopped = None;
+        self.pruned_hops = false;
+    }
+
+    fn start(&mut self, from: NodeId, alpha: Vec<f64>, max_cost: Cost) {
+        self.reset_state();
+        self.last_from = from;
+        self.last_pref = alpha;
+        self.max_cost = max_cost;
+        self.heap.push(MinHeapItem::new(from, 0.0, None));
     }
 
     /// return path of edges(!) from source to target not path of nodes!
@@ -46,25 +96,83 @@ impl NDijkstra {
         offset: &[EdgeId],
         edges: &[Edge],
     ) -> Option<(Vec<NodeId>, Vec<Cost>)> {
-        if self.last_from == from && same_array(&self.last_pref, &alpha) {
-            if self.visited.is_valid(to) {
-                return Some(self.resolve_path(to, edges));
-            }
+        // If something changed, we initialize it normally
+        if !self.is_searching(from, &alpha) {
+            self.start(from, alpha, COST_MAX);
+        }
+        if self.settle(to, offset, edges) {
+            Some(self.resolve_path(to, edges))
+        } else {
+            None
+        }
+    }
+
+    /// one search for all targets, which stops at `max_cost` if the limits have a cost bound.
+    /// afterwards `find_path` answers the targets without searching again
+    pub fn find_paths(
+        &mut self,
+        from: NodeId,
+        targets: &[NodeId],
+        alpha: Vec<f64>,
+        max_cost: Cost,
+        offset: &[EdgeId],
+        edges: &[Edge],
+    ) {
+        let max_cost = if self.limits.cost_bound {
+            max_cost
         } else {
-            // If something changed, we initialize it normally
-            self.reset_state();
-            self.last_from = from;
-            self.last_pref = alpha.clone();
+            COST_MAX
+        };
+        self.start(from, alpha, max_cost);
+        for target in targets {
+            self.settle(*target, offset, edges);
+        }
+    }
+
+    /// the current search starts at `from` with the same alpha
+    pub fn is_searching(&self, from: NodeId, alpha: &[f64]) -> bool {
+        self.last_from == from && same_array(&self.last_pref, alpha)
+    }
 
-            self.heap.push(MinHeapItem::new(from, 0.0, None));
+    /// the limit, that stopped the search before reaching `to` or pruned edges on the way.
+    /// `None` means the result is the same as without limits
+    pub fn limited_by(&self, to: NodeId) -> Option<WitnessLimit> {
+        match self.stopped {
+            Some(limit) if !self.visited.is_valid(to) => Some(limit),
+            _ if self.pruned_hops => Some(WitnessLimit::Hops),
+            _ => None,
+        }
+    }
+
+    /// continue the current search until `to` is settled or a limit stops it
+    fn settle(&mut self, to: NodeId, offset: &[EdgeId], edges: &[Edge]) -> bool {
+        if self.visited.is_valid(to) {
+            return true;
+        }
+        if self.stopped.is_some() {
+            return false;
         }
 
         while let Some(MinHeapItem {
             node,
             dist,
             prev_edge,
-        }) = self.heap.pop()
+        }) = self.heap.peek().copied()
         {
+            if dist > self.max_cost {
+                self.stopped = Some(WitnessLimit::Cost);
+                return false;
+            }
+            if self
+                .limits
+                .max_settled_nodes
+                .is_some_and(|max| self.settled_nodes >= max)
+            {
+                self.stopped = Some(WitnessLimit::SettledNodes);
+                return false;
+            }
+            self.heap.pop();
+
             // node has already been visited and can be skipped
             // replacement for decrease key operation
             if self.visited.is_valid(node) && dist > self.dist[node].0 {
@@ -73,22 +181,32 @@ impl NDijkstra {
 
             self.visited.set_valid(node);
             self.dist[node] = (dist, prev_edge);
-
-            for edge in graph_helper::get_up_edge_ids(node, offset) {
-                let new_edge = &edges[edge];
-                let alt = dist + costs_by_alpha(&new_edge.cost, &alpha);
-                if !self.visited.is_valid(new_edge.to) || alt < self.dist[new_edge.to].0 {
-                    self.heap
-                        .push(MinHeapItem::new(new_edge.to, alt, Some(edge)));
+            self.hops[node] = prev_edge.map_or(0, |edge| self.hops[edges[edge].from] + 1);
+            self.settled_nodes += 1;
+
+            if self
+                .limits
+                .max_hops
+                .is_some_and(|max| self.hops[node] >= max)
+            {
+                self.pruned_hops |= !graph_helper::get_up_edge_ids(node, offset).is_empty();
+            } else {
+                for edge in graph_helper::get_up_edge_ids(node, offset) {
+                    let new_edge = &edges[edge];
+                    let alt = dist + costs_by_alpha(&new_edge.cost, &self.last_pref);
+                    if !self.visited.is_valid(new_edge.to) || alt < self.dist[new_edge.to].0 {
+                        self.heap
+                            .push(MinHeapItem::new(new_edge.to, alt, Some(edge)));
+                    }
                 }
             }
 
             // found end
             if node == to {
-                return Some(self.resolve_path(to, edges));
+                return true;
             }
         }
-        None
+        false
     }
 
     /// recreate path backwards
@@ -301,7 +419,7 @@ fn multiple_paths() {
     assert!(result.is_some());
     let path = result.unwrap();
     assert_eq!(path.0.len(), 0);
-    assert_eq!(path.0, []);
+    assert_eq!(path.0, Vec::<EdgeId>::new());
     assert_eq!(path.1, vec![0.0]);
 
     let result = d.find_path(6, 3, vec![1.0], &up_offset, &edges);
@@ -361,3 +479,72 @@ fn ndim() {
     assert_eq!(path.0, [0, 2, 3]);
     assert_eq!(path.1, vec![27.0, 3.0]);
 }
+
+#[test]
+fn limits() {
+    // same graph as in `shortest_path`, the shortest path has 4 edges
+    //
+    // 0-9->1-9->2
+    // |         A
+    // 1         |
+    // |         1
+    // V         |
+    // 3-1->4-1->5
+
+    let amount_nodes = 6;
+
+    let mut edges = vec![
+        Edge::new(0, 1, vec![9.0]),
+        Edge::new(1, 2, vec![9.0]),
+        Edge::new(0, 3, vec![1.0]),
+        Edge::new(3, 4, vec![1.0]),
+        Edge::new(4, 5, vec![1.0]),
+        Edge::new(5, 2, vec![1.0]),
+    ];
+
+    let mut up_offset = Vec::<EdgeId>::new();
+    let mut down_offset = Vec::<EdgeId>::new();
+    offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, amount_nodes);
+
+    let limits = WitnessLimits {
+        max_hops: Some(2),
+        ..WitnessLimits::default()
+    };
+    let mut d = NDijkstra::with_limits(amount_nodes, 1, limits);
+    let result = d.find_path(0, 2, vec![1.0], &up_offset, &edges);
+    assert_eq!(result.unwrap().1, vec![18.0]);
+    assert_eq!(d.limited_by(2), Some(WitnessLimit::Hops));
+
+    let limits = WitnessLimits {
+        max_settled_nodes: Some(3),
+        ..WitnessLimits::default()
+    };
+    let mut d = NDijkstra::with_limits(amount_nodes, 1, limits);
+    assert!(d.find_path(0, 2, vec![1.0], &up_offset, &edges).is_none());
+    assert_eq!(d.limited_by(2), Some(WitnessLimit::SettledNodes));
+    assert!(d.find_path(0, 4, vec![1.0], &up_offset, &edges).is_some());
+    assert_eq!(d.limited_by(4), None);
+
+    let limits = WitnessLimits {
+        cost_bound: true,
+        ..WitnessLimits::default()
+    };
+    let mut d = NDijkstra::with_limits(amount_nodes, 1, limits);
+    d.find_paths(0, &[5, 2], vec![1.0], 3.0, &up_offset, &edges);
+    assert_eq!(d.limited_by(5), None);
+    assert_eq!(d.limited_by(2), Some(WitnessLimit::Cost));
+    assert_eq!(
+        d.find_path(0, 5, vec![1.0], &up_offset, &edges).unwrap().1,
+        vec![3.0]
+    );
+    assert!(d.find_path(0, 2, vec![1.0], &up_offset, &edges).is_none());
+
+    // without limits a single search answers all targets
+    let mut d = NDijkstra::new(amount_nodes, 1);
+    d.find_paths(0, &[5, 2], vec![1.0], 3.0, &up_offset, &edges);
+    assert_eq!(d.limited_by(2), None);
+    assert_eq!(
+        d.find_path(0, 2, vec![1.0], &up_offset, &edges).unwrap().1,
+        vec![4.0]
+    );
+}
@@ -46,7 +46,7 @@ pub fn generate_offsets_unstable(
 }
 
 pub fn generate_offsets(
-    edges: &mut Vec<Edge>,
+    edges: &mut [Edge],
     up_offset: &mut Vec<EdgeId>,
     down_offset: &mut Vec<EdgeId>,
     amount_nodes: usize,
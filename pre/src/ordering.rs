@@ -1,68 +1,63 @@
 use super::*;
+use heuristic::{Heuristic, OrderingGraph, Priority, WitnessSearch};
 use valid_flag::ValidFlag;
 
 use std::collections::BTreeSet;
 
-/// heuristic by neighbors
-#[allow(dead_code)]
-pub fn calculate_single_heuristic(
-    node: NodeId,
-    _deleted_neighbors: &[NodeId],
-    up_offset: &[EdgeId],
-    down_offset: &[EdgeId],
-) -> usize {
-    (up_offset[node + 1] - up_offset[node]) * (down_offset[node + 1] - down_offset[node])
-    // + _deleted_neighbors[node]
-}
-
-/// calculate heuristic in parallel
-#[allow(clippy::too_many_arguments)]
+/// calculate heuristics of the level in parallel, nodes of other levels are never chosen
 pub fn calculate_heuristics(
     level_height: Level,
     nodes: &[Node],
-    deleted_neighbors: &[NodeId],
-    up_offset: &[EdgeId],
-    down_offset: &[EdgeId],
-) -> Vec<usize> {
-    let mut heuristics: Vec<usize> = Vec::with_capacity(nodes.len());
-    for (node_id, node) in nodes.iter().enumerate() {
-        if node.level != level_height {
-            heuristics.push(usize::MAX);
-        } else {
-            heuristics.push(calculate_single_heuristic(
-                node_id,
-                deleted_neighbors,
-                up_offset,
-                down_offset,
-            ));
-        }
-    }
-    heuristics
+    graph: &OrderingGraph,
+    heuristic: &dyn Heuristic,
+    dim: usize,
+) -> Vec<Priority> {
+    nodes
+        .par_iter()
+        .enumerate()
+        .map_init(
+            || WitnessSearch::new(nodes.len(), dim),
+            |witness, (node_id, node)| {
+                if node.level != level_height {
+                    Priority::MAX
+                } else {
+                    heuristic.priority(node_id, graph, witness)
+                }
+            },
+        )
+        .collect()
 }
 
 /// update all direct neighbors
-#[allow(clippy::too_many_arguments)]
 pub fn update_neighbor_heuristics(
     neighbors: Vec<NodeId>,
     level_height: Level,
-    heuristics: &mut [usize],
+    heuristics: &mut [Priority],
     nodes: &[Node],
-    deleted_neighbors: &[NodeId],
-    up_offset: &[EdgeId],
-    down_offset: &[EdgeId],
+    graph: &OrderingGraph,
+    heuristic: &dyn Heuristic,
+    dim: usize,
 ) {
-    for neighbor in neighbors {
-        if nodes[neighbor].level == level_height {
-            heuristics[neighbor] =
-                calculate_single_heuristic(neighbor, deleted_neighbors, up_offset, down_offset);
-        }
+    let priorities: Vec<(NodeId, Priority)> = neighbors
+        .par_iter()
+        .filter(|&&neighbor| nodes[neighbor].level == level_height)
+        .map_init(
+            || WitnessSearch::new(nodes.len(), dim),
+            |witness, &neighbor| (neighbor, heuristic.priority(neighbor, graph, witness)),
+        )
+        .collect();
+    for (neighbor, priority) in priorities {
+        heuristics[neighbor] = priority;
     }
 }
 
-/// get independent set of graph using heuristic
+/// get independent set of graph using heuristic,
+/// only the lowest 1/`candidates` of the remaining nodes are considered
+#[allow(clippy::too_many_arguments)]
 pub fn get_independent_set(
     remaining_nodes: &BTreeSet<NodeId>,
-    heuristics: &[usize],
+    heuristics: &[Priority],
+    candidates: usize,
     minimas_bool: &mut ValidFlag,
     edges: &[Edge],
     up_offset: &[EdgeId],
@@ -74,8 +69,8 @@ pub fn get_independent_set(
         // sort remaining_nodes via heuristic
         remaining_nodes_vector.par_sort_unstable_by_key(|&node| heuristics[node]);
 
-        // take lower 1/10 and round up by adding the divider minus one
-        remaining_nodes_vector[0..((remaining_nodes_vector.len() + 10 - 1) / 10)].to_vec()
+        // take lower 1/candidates rounded up
+        remaining_nodes_vector[0..remaining_nodes_vector.len().div_ceil(candidates)].to_vec()
     } else {
         remaining_nodes_vector
     };
@@ -142,6 +137,7 @@ fn independent_set_test() {
     let minima = get_independent_set(
         &remaining_nodes,
         &heuristics,
+        10,
         &mut minimas_bool,
         &edges,
         &up_offset,
@@ -161,6 +157,7 @@ fn independent_set_test() {
     let minima = get_independent_set(
         &remaining_nodes,
         &heuristics,
+        10,
         &mut minimas_bool,
         &edges,
         &up_offset,
@@ -0,0 +1,787 @@
+use super::*;
+use checkpoint::{Checkpoint, CheckpointRef};
+use heuristic::{Heuristic, NodeOrdering};
+use report::Report;
+use std::fmt;
+use std::time::Instant;
+
+#[derive(Debug, PartialEq)]
+pub enum PipelineError {
+    EmptyGraph,
+    InvalidEdge {
+        edge_id: EdgeId,
+        amount_nodes: usize,
+    },
+    CostDimension {
+        edge_id: EdgeId,
+        expected: usize,
+        found: usize,
+    },
+    PartitionLength {
+        expected: usize,
+        found: usize,
+    },
+    InvalidPartition {
+        node_id: NodeId,
+        partition: PartitionId,
+        amount: usize,
+    },
+    InvalidContractionStop(f64),
+    InvalidCandidates(usize),
+    Checkpoint(String),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::EmptyGraph => write!(f, "graph has no nodes or no edges"),
+            PipelineError::InvalidEdge {
+                edge_id,
+                amount_nodes,
+            } => write!(
+                f,
+                "edge {} leads to a node outside of the {} nodes",
+                edge_id, amount_nodes
+            ),
+            PipelineError::CostDimension {
+                edge_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "edge {} has {} costs, but the graph has {} metrics",
+                edge_id, found, expected
+            ),
+            PipelineError::PartitionLength { expected, found } => write!(
+                f,
+                "partition has {} entries, but the graph has {} nodes",
+                found, expected
+            ),
+            PipelineError::InvalidPartition {
+                node_id,
+                partition,
+                amount,
+            } => write!(
+                f,
+                "partition {} of node {} is not below the {} partitions of the mlp-levels",
+                partition, node_id, amount
+            ),
+            PipelineError::InvalidContractionStop(stop) => {
+                write!(f, "contraction stop {} has to be in (0, 1]", stop)
+            }
+            PipelineError::InvalidCandidates(candidates) => {
+                write!(f, "candidates {} has to be at least 1", candidates)
+            }
+            PipelineError::Checkpoint(error) => write!(f, "checkpoint: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// builds a bin-file from a graph and an optional multi-level-partition.
+/// without partition all nodes are in a single partition
+pub struct Pipeline {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    metrics: Vec<String>,
+    mlp_levels: Vec<usize>,
+    partitions: Option<Vec<PartitionId>>,
+    contraction_stop: f64,
+    node_ordering: NodeOrdering,
+    witness_limits: WitnessLimits,
+    cell_contraction: bool,
+    checkpoint_file: Option<String>,
+    checkpoint_every: Option<usize>,
+    resume: bool,
+    verbose: bool,
+}
+
+impl Pipeline {
+    /// graph in memory, every edge needs one cost per metric
+    pub fn new(nodes: Vec<Node>, edges: Vec<Edge>, metrics: Vec<String>) -> Self {
+        Pipeline {
+            nodes,
+            edges,
+            metrics,
+            mlp_levels: vec![1],
+            partitions: None,
+            contraction_stop: 1.0,
+            node_ordering: NodeOrdering::default(),
+            witness_limits: WitnessLimits::default(),
+            cell_contraction: false,
+            checkpoint_file: None,
+            checkpoint_every: None,
+            resume: false,
+            verbose: false,
+        }
+    }
+
+    /// graph from a pbfextractor file
+    pub fn from_fmi_file(file_path: &str) -> std::io::Result<Self> {
+        let mut nodes = Vec::<Node>::new();
+        let mut edges = Vec::<Edge>::new();
+        let mut metrics = Vec::<String>::new();
+        fmi_import::read_file(file_path, &mut nodes, &mut edges, &mut metrics)?;
+        Ok(Pipeline::new(nodes, edges, metrics))
+    }
+
+    /// partition of every node, numbered from the lowest level up like in mlp-files
+    pub fn partition(mut self, mlp_levels: Vec<usize>, partitions: Vec<PartitionId>) -> Self {
+        self.mlp_levels = mlp_levels;
+        self.partitions = Some(partitions);
+        self
+    }
+
+    /// partition from a mlp-file
+    pub fn mlp_file(mut self, file_path: &str) -> std::io::Result<Self> {
+        let mut mlp_levels = Vec::<usize>::new();
+        mlp_import::read_file(file_path, &mut self.nodes, &mut mlp_levels)?;
+        let partitions = self.nodes.iter().map(|node| node.partition).collect();
+        Ok(self.partition(mlp_levels, partitions))
+    }
+
+    /// share of nodes to contract, only used without partition
+    pub fn contraction_stop(mut self, contraction_stop: f64) -> Self {
+        self.contraction_stop = contraction_stop;
+        self
+    }
+
+    /// priority of the nodes for the contraction, by default in-degree times out-degree
+    pub fn heuristic(mut self, heuristic: Box<dyn Heuristic>) -> Self {
+        self.node_ordering.heuristic = heuristic;
+        self
+    }
+
+    /// contract only nodes among the lowest 1/`candidates` of the remaining nodes, by default 10.
+    /// smaller values contract more nodes per rank at the expense of the order
+    pub fn candidates(mut self, candidates: usize) -> Self {
+        self.node_ordering.candidates = candidates;
+        self
+    }
+
+    /// limits of the witness searches deciding which shortcuts are needed, by default unlimited.
+    /// limits speed up the contraction of dense cores, but can add unneeded shortcuts
+    pub fn witness_limits(mut self, witness_limits: WitnessLimits) -> Self {
+        self.witness_limits = witness_limits;
+        self
+    }
+
+    /// contract the cells of the levels below the top in parallel, off by default.
+    /// a cell does not find witnesses outside of it, which can add shortcuts
+    pub fn cell_contraction(mut self, cell_contraction: bool) -> Self {
+        self.cell_contraction = cell_contraction;
+        self
+    }
+
+    /// write the state of the contraction after every level and every `every_ranks` ranks
+    pub fn checkpoint(mut self, file_path: &str, every_ranks: Option<usize>) -> Self {
+        self.checkpoint_file = Some(file_path.to_string());
+        self.checkpoint_every = every_ranks;
+        self
+    }
+
+    /// continue from the checkpoint file instead of starting over,
+    /// the result is the same as without interruption
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// print the duration of every step
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    fn validate(&self) -> Result<(), PipelineError> {
+        if self.nodes.is_empty() || self.edges.is_empty() {
+            return Err(PipelineError::EmptyGraph);
+        }
+        let dim = self.edges[0].cost.len();
+        let expected = if self.metrics.is_empty() {
+            dim
+        } else {
+            self.metrics.len()
+        };
+        for (edge_id, edge) in self.edges.iter().enumerate() {
+            if edge.from >= self.nodes.len() || edge.to >= self.nodes.len() {
+                return Err(PipelineError::InvalidEdge {
+                    edge_id,
+                    amount_nodes: self.nodes.len(),
+                });
+            }
+            if edge.cost.len() != expected {
+                return Err(PipelineError::CostDimension {
+                    edge_id,
+                    expected,
+                    found: edge.cost.len(),
+                });
+            }
+        }
+        if let Some(partitions) = &self.partitions {
+            if partitions.len() != self.nodes.len() {
+                return Err(PipelineError::PartitionLength {
+                    expected: self.nodes.len(),
+                    found: partitions.len(),
+                });
+            }
+            let amount = self.mlp_levels.iter().product::<usize>();
+            if let Some((node_id, partition)) = partitions
+                .iter()
+                .enumerate()
+                .find(|(_, partition)| **partition >= amount)
+            {
+                return Err(PipelineError::InvalidPartition {
+                    node_id,
+                    partition: *partition,
+                    amount,
+                });
+            }
+        }
+        if !(self.contraction_stop > 0.0 && self.contraction_stop <= 1.0) {
+            return Err(PipelineError::InvalidContractionStop(self.contraction_stop));
+        }
+        if self.node_ordering.candidates == 0 {
+            return Err(PipelineError::InvalidCandidates(0));
+        }
+        if self.checkpoint_every == Some(0) {
+            return Err(PipelineError::Checkpoint(
+                "checkpoints have to be written every 1 or more ranks".to_string(),
+            ));
+        }
+        if self.cell_contraction && self.checkpoint_every.is_some() {
+            return Err(PipelineError::Checkpoint(
+                "checkpoints every n ranks can not be written while contracting cells".to_string(),
+            ));
+        }
+        if self.resume && self.checkpoint_file.is_none() {
+            return Err(PipelineError::Checkpoint(
+                "resuming needs a checkpoint file".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// a partition has to contract all nodes
+    fn effective_contraction_stop(&self) -> f64 {
+        match self.partitions {
+            Some(_) => 1.0,
+            None => self.contraction_stop,
+        }
+    }
+
+    /// checkpoints are only resumed for the same edges, costs and partition
+    fn graph_hash(&self) -> u64 {
+        checkpoint::graph_hash(&self.edges, self.partitions.as_deref().unwrap_or(&[]))
+    }
+
+    /// contract the graph and generate everything the query needs
+    pub fn run(self) -> Result<BinFile, PipelineError> {
+        self.run_with_report().map(|(bin_file, _)| bin_file)
+    }
+
+    /// like `run` with statistics of every phase, level and rank
+    pub fn run_with_report(self) -> Result<(BinFile, Report), PipelineError> {
+        self.validate()?;
+        let resume = match (&self.checkpoint_file, self.resume) {
+            (Some(file_path), true) => {
+                let checkpoint = Checkpoint::read_file(file_path).map_err(|error| {
+                    PipelineError::Checkpoint(format!(
+                        "error while reading '{}': {}",
+                        file_path, error
+                    ))
+                })?;
+                if !checkpoint.matches(
+                    self.nodes.len(),
+                    self.graph_hash(),
+                    &self.mlp_levels,
+                    self.effective_contraction_stop(),
+                    &self.node_ordering,
+                    self.witness_limits,
+                    self.cell_contraction,
+                ) {
+                    return Err(PipelineError::Checkpoint(format!(
+                        "'{}' was written for another graph, partition, heuristic or witness limits",
+                        file_path
+                    )));
+                }
+                Some(checkpoint)
+            }
+            _ => None,
+        };
+        let checkpoint_file = self.checkpoint_file.clone();
+        let every_ranks = self.checkpoint_every;
+        self.contract(
+            every_ranks,
+            resume,
+            &mut |checkpoint| match &checkpoint_file {
+                Some(file_path) => checkpoint::write_file(file_path, checkpoint),
+                None => Ok(()),
+            },
+        )
+    }
+
+    fn contract(
+        self,
+        every_ranks: Option<usize>,
+        resume: Option<Checkpoint>,
+        save: &mut dyn FnMut(&CheckpointRef) -> bincode::Result<()>,
+    ) -> Result<(BinFile, Report), PipelineError> {
+        let contraction_stop = self.effective_contraction_stop();
+        let graph_hash = self.graph_hash();
+        let Pipeline {
+            mut nodes,
+            mut edges,
+            metrics,
+            mlp_levels,
+            partitions,
+            node_ordering,
+            witness_limits,
+            cell_contraction,
+            verbose,
+            ..
+        } = self;
+        let mut report = Report {
+            amount_nodes: nodes.len(),
+            amount_edges: edges.len(),
+            mlp_levels: mlp_levels.clone(),
+            ..Report::default()
+        };
+        let mut log = |step: &str, time: Instant| {
+            if verbose {
+                println!("{} in: {:?}", step, time.elapsed());
+            }
+            report.add_phase(step, time.elapsed());
+        };
+
+        match &partitions {
+            Some(partitions) => {
+                for (node, partition) in nodes.iter_mut().zip(partitions) {
+                    node.partition = *partition;
+                }
+            }
+            None => nodes.iter_mut().for_each(|node| node.partition = 0),
+        }
+
+        let mut up_offset = Vec::<EdgeId>::new();
+        let mut down_offset = Vec::<EdgeId>::new();
+
+        let mut down_index =
+            offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, nodes.len());
+
+        let highest_diff_time = Instant::now();
+        mlp_helper::calculate_levels(
+            &mut nodes,
+            &edges,
+            &up_offset,
+            &down_offset,
+            &down_index,
+            &mlp_levels,
+        );
+        log("mlp levels", highest_diff_time);
+
+        let contraction_time = Instant::now();
+        let ranks = contraction::prp_contraction(
+            &mut nodes,
+            &mut edges,
+            &mut up_offset,
+            &mut down_offset,
+            &mut down_index,
+            &mlp_levels,
+            contraction_stop,
+            &node_ordering,
+            witness_limits,
+            cell_contraction,
+            graph_hash,
+            every_ranks,
+            resume,
+            save,
+        )
+        .map_err(|error| {
+            PipelineError::Checkpoint(format!("error while writing checkpoint: {}", error))
+        })?;
+        log("contraction", contraction_time);
+
+        let components_time = Instant::now();
+        let strong_components = components::strongly_connected(nodes.len(), &edges, &up_offset);
+        let weak_components = components::weakly_connected(nodes.len(), &edges);
+        log("components", components_time);
+
+        let edge_order_time = Instant::now();
+        let edge_orders =
+            edge_order::generate_edge_orders(&nodes, &edges, &up_offset, &down_offset, &down_index);
+        log("edge orders", edge_order_time);
+
+        let edge_costs: Vec<Cost> = edges.iter().flat_map(|e| e.cost.clone()).collect();
+
+        let grid_time = Instant::now();
+        let mut grid_offset = Vec::<GridId>::new();
+        let mut grid = Vec::<NodeId>::new();
+
+        let grid_bounds = grid::generate_grid(&mut grid, &mut grid_offset, &nodes);
+        log("grid", grid_time);
+
+        report.ranks = ranks;
+        report.add_levels(&nodes, &edges);
+
+        let bin_file = BinFile {
+            nodes,
+            mlp_levels,
+            edges,
+            edge_costs,
+            up_offset,
+            down_offset,
+            down_index,
+            grid_offset,
+            grid,
+            grid_bounds,
+            metrics,
+            strong_components,
+            weak_components,
+            edge_orders,
+        };
+        Ok((bin_file, report))
+    }
+}
+
+#[cfg(test)]
+fn grid_graph(size: usize) -> (Vec<Node>, Vec<Edge>) {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            nodes.push(Node::new(48.0 + y as Angle * 0.01, 9.0 + x as Angle * 0.01));
+            let id = y * size + x;
+            // distance and a second metric preferring the left and top border
+            if x + 1 < size {
+                let cost = vec![1.0, 1.0 + y as Cost];
+                edges.push(Edge::new(id, id + 1, cost.clone()));
+                edges.push(Edge::new(id + 1, id, cost));
+            }
+            if y + 1 < size {
+                let cost = vec![1.0, 1.0 + x as Cost];
+                edges.push(Edge::new(id, id + size, cost.clone()));
+                edges.push(Edge::new(id + size, id, cost));
+            }
+        }
+    }
+    (nodes, edges)
+}
+
+/// four quadrants on the lowest level, all in one on the upper level
+#[cfg(test)]
+pub(crate) fn quadrant_pipeline(size: usize) -> Pipeline {
+    let (nodes, edges) = grid_graph(size);
+    let partitions = (0..size * size)
+        .map(|id| (id / size >= size / 2) as usize * 2 + (id % size >= size / 2) as usize)
+        .collect();
+    let metrics = vec!["distance".to_string(), "border".to_string()];
+    Pipeline::new(nodes, edges, metrics).partition(vec![4, 1], partitions)
+}
+
+#[test]
+fn pipeline() {
+    let size = 6;
+    let amount_edges = grid_graph(size).1.len();
+
+    let (result, report) = quadrant_pipeline(size).run_with_report().unwrap();
+
+    assert_eq!(result.nodes.len(), size * size);
+    assert_eq!(result.up_offset.len(), size * size + 1);
+    assert_eq!(result.edge_costs.len(), result.edges.len() * 2);
+    assert_eq!(
+        result
+            .edges
+            .iter()
+            .filter(|edge| edge.contracted_edges.is_none())
+            .count(),
+        amount_edges
+    );
+    // every shortcut costs as much as the edges it replaces
+    for edge in &result.edges {
+        if let Some((a, b)) = edge.contracted_edges {
+            assert_eq!(edge.from, result.edges[a].from);
+            assert_eq!(result.edges[a].to, result.edges[b].from);
+            assert_eq!(result.edges[b].to, edge.to);
+            for (i, cost) in edge.cost.iter().enumerate() {
+                assert_eq!(*cost, result.edges[a].cost[i] + result.edges[b].cost[i]);
+            }
+        }
+    }
+    // all nodes got a rank and the grid is connected in both directions
+    assert!(result.nodes.iter().all(|node| node.rank != INVALID_RANK));
+    assert!(result.strong_components.iter().all(|c| *c == 0));
+    assert_eq!(result.edge_orders.prp.up.len(), result.edges.len());
+    assert_eq!(result.grid.len(), size * size);
+
+    // the report covers every contracted node and every shortcut
+    assert_eq!(report.phases.len(), 5);
+    assert_eq!(
+        report
+            .ranks
+            .iter()
+            .map(|rank| rank.independent_set)
+            .sum::<usize>(),
+        size * size
+    );
+    assert_eq!(report.levels.len(), 3);
+    assert_eq!(
+        report.levels.iter().map(|level| level.edges).sum::<usize>(),
+        result.edges.len()
+    );
+    assert_eq!(report.amount_shortcuts, result.edges.len() - amount_edges);
+}
+
+#[test]
+fn pipeline_heuristics() {
+    let size = 6;
+    for heuristic in &[
+        "degree",
+        "edge-difference",
+        "deleted-neighbors",
+        "depth",
+        "edge-difference=2,deleted-neighbors=1,depth=1",
+    ] {
+        for candidates in &[1, 10] {
+            let result = quadrant_pipeline(size)
+                .heuristic(heuristic::parse_heuristic(heuristic).unwrap())
+                .candidates(*candidates)
+                .run()
+                .unwrap();
+            assert!(result.nodes.iter().all(|node| node.rank != INVALID_RANK));
+            for edge in &result.edges {
+                if let Some((a, b)) = edge.contracted_edges {
+                    assert_eq!(edge.from, result.edges[a].from);
+                    assert_eq!(result.edges[b].to, edge.to);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pipeline_witness_limits() {
+    let size = 6;
+    let unlimited = quadrant_pipeline(size).run().unwrap();
+    let expected = bincode::serialize(&unlimited).unwrap();
+
+    // the cost bound never misses a witness
+    let result = quadrant_pipeline(size)
+        .witness_limits(WitnessLimits {
+            cost_bound: true,
+            ..WitnessLimits::default()
+        })
+        .run()
+        .unwrap();
+    assert_eq!(bincode::serialize(&result).unwrap(), expected);
+
+    let (result, report) = quadrant_pipeline(size)
+        .witness_limits(WitnessLimits {
+            max_hops: Some(1),
+            max_settled_nodes: Some(4),
+            cost_bound: true,
+        })
+        .run_with_report()
+        .unwrap();
+    assert!(result.nodes.iter().all(|node| node.rank != INVALID_RANK));
+    for rank in &report.ranks {
+        assert!(rank.hop_limited_shortcuts <= rank.shortcuts);
+        assert!(rank.settled_limited_shortcuts <= rank.shortcuts);
+    }
+    // without a witness of more than one edge every path over two edges needs a shortcut
+    assert!(report
+        .ranks
+        .iter()
+        .any(|rank| rank.hop_limited_shortcuts > 0));
+    assert!(
+        report.amount_shortcuts
+            >= unlimited
+                .edges
+                .iter()
+                .filter(|edge| edge.contracted_edges.is_some())
+                .count()
+    );
+}
+
+#[test]
+fn cell_contraction() {
+    let size = 8;
+    for cell_contraction in &[true, false] {
+        let (result, report) = quadrant_pipeline(size)
+            .cell_contraction(*cell_contraction)
+            .run_with_report()
+            .unwrap();
+        assert_eq!(
+            report
+                .ranks
+                .iter()
+                .map(|rank| rank.independent_set)
+                .sum::<usize>(),
+            size * size
+        );
+        for edge in &result.edges {
+            // every rank is an independent set
+            assert_ne!(result.nodes[edge.from].rank, result.nodes[edge.to].rank);
+            if let Some((a, b)) = edge.contracted_edges {
+                assert_eq!(edge.from, result.edges[a].from);
+                assert_eq!(result.edges[a].to, result.edges[b].from);
+                assert_eq!(result.edges[b].to, edge.to);
+                for (i, cost) in edge.cost.iter().enumerate() {
+                    assert_eq!(*cost, result.edges[a].cost[i] + result.edges[b].cost[i]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cell_contraction_stop() {
+    let size = 8;
+    let mlp_levels = vec![4, 1];
+    let partitions = quadrant_pipeline(size).partitions.unwrap();
+    let (mut nodes, mut edges) = grid_graph(size);
+    for (node, partition) in nodes.iter_mut().zip(partitions) {
+        node.partition = partition;
+    }
+    let mut up_offset = Vec::<EdgeId>::new();
+    let mut down_offset = Vec::<EdgeId>::new();
+    let mut down_index =
+        offset::generate_offsets(&mut edges, &mut up_offset, &mut down_offset, nodes.len());
+    mlp_helper::calculate_levels(
+        &mut nodes,
+        &edges,
+        &up_offset,
+        &down_offset,
+        &down_index,
+        &mlp_levels,
+    );
+    let ranks = contraction::prp_contraction(
+        &mut nodes,
+        &mut edges,
+        &mut up_offset,
+        &mut down_offset,
+        &mut down_index,
+        &mlp_levels,
+        0.3,
+        &NodeOrdering::default(),
+        WitnessLimits::default(),
+        true,
+        0,
+        None,
+        None,
+        &mut |_| Ok(()),
+    )
+    .unwrap();
+    let contracted = ranks.iter().map(|rank| rank.independent_set).sum::<usize>();
+    // more than 30% of all nodes are contracted on every level
+    assert!(contracted > size * size * 3 / 5 && contracted < size * size);
+    let contracted_in_cells = ranks
+        .iter()
+        .filter(|rank| rank.level == 0)
+        .map(|rank| rank.independent_set)
+        .sum::<usize>();
+    assert!(contracted_in_cells > size * size * 3 / 10);
+    assert!(contracted_in_cells < nodes.iter().filter(|node| node.level == 0).count());
+}
+
+#[test]
+fn pipeline_validation() {
+    let (nodes, edges) = grid_graph(2);
+    let metrics = vec!["distance".to_string(), "border".to_string()];
+    let error = Pipeline::new(nodes, edges, metrics.clone())
+        .partition(vec![2], vec![0, 1, 2, 0])
+        .run()
+        .err();
+    assert_eq!(
+        error,
+        Some(PipelineError::InvalidPartition {
+            node_id: 2,
+            partition: 2,
+            amount: 2
+        })
+    );
+
+    let (nodes, mut edges) = grid_graph(2);
+    edges[1].cost.pop();
+    let error = Pipeline::new(nodes, edges, metrics).run().err();
+    assert_eq!(
+        error,
+        Some(PipelineError::CostDimension {
+            edge_id: 1,
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn resume() {
+    let size = 6;
+    let mut checkpoints = Vec::new();
+    let expected = quadrant_pipeline(size)
+        .contract(Some(1), None, &mut |checkpoint| {
+            checkpoints.push(bincode::serialize(checkpoint)?);
+            Ok(())
+        })
+        .unwrap()
+        .0;
+    let expected = bincode::serialize(&expected).unwrap();
+    assert!(checkpoints.len() > 2);
+
+    // resuming after any rank or level gives the same bin-file
+    for checkpoint in &checkpoints {
+        let checkpoint: Checkpoint = bincode::deserialize(checkpoint).unwrap();
+        let result = quadrant_pipeline(size)
+            .contract(None, Some(checkpoint), &mut |_| Ok(()))
+            .unwrap()
+            .0;
+        assert_eq!(bincode::serialize(&result).unwrap(), expected);
+    }
+
+    // and via the checkpoint file
+    let file_path = std::env::temp_dir().join(format!("prp_pre_resume_{}", std::process::id()));
+    let file_path = file_path.to_str().unwrap();
+    quadrant_pipeline(size)
+        .checkpoint(file_path, Some(3))
+        .run()
+        .unwrap();
+    let result = quadrant_pipeline(size)
+        .checkpoint(file_path, None)
+        .resume(true)
+        .run()
+        .unwrap();
+    assert_eq!(bincode::serialize(&result).unwrap(), expected);
+
+    // a graph with the same amount of nodes but other costs does not resume
+    let (nodes, mut edges) = grid_graph(size);
+    edges[0].cost[0] = 2.0;
+    let partitions = quadrant_pipeline(size).partitions;
+    let error = Pipeline::new(nodes, edges, Vec::new())
+        .partition(vec![4, 1], partitions.unwrap())
+        .checkpoint(file_path, None)
+        .resume(true)
+        .run()
+        .err();
+    std::fs::remove_file(file_path).unwrap();
+    assert!(matches!(error, Some(PipelineError::Checkpoint(_))));
+
+    let error = Pipeline::new(grid_graph(size).0, grid_graph(size).1, Vec::new())
+        .resume(true)
+        .run()
+        .err();
+    assert!(matches!(error, Some(PipelineError::Checkpoint(_))));
+
+    let error = quadrant_pipeline(size)
+        .checkpoint(file_path, Some(0))
+        .run()
+        .err();
+    assert!(matches!(error, Some(PipelineError::Checkpoint(_))));
+
+    let error = quadrant_pipeline(size)
+        .cell_contraction(true)
+        .checkpoint(file_path, Some(3))
+        .run()
+        .err();
+    assert!(matches!(error, Some(PipelineError::Checkpoint(_))));
+}
@@ -0,0 +1,240 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::Duration;
+
+/// statistics of contracting one independent set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RankReport {
+    pub rank: Rank,
+    pub level: Level,
+    pub remaining_nodes: usize,
+    pub independent_set: usize,
+    pub edges: usize,
+    pub shortcuts: usize,
+    /// shortcuts, whose witness search was cut by the hop limit
+    pub hop_limited_shortcuts: usize,
+    /// shortcuts, whose witness search was stopped by the settled-node limit
+    pub settled_limited_shortcuts: usize,
+    pub removed_edges: usize,
+    pub resulting_edges: usize,
+    pub seconds: f64,
+}
+
+/// statistics of one level, the level above the highest mlp-level holds the remaining core
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LevelReport {
+    pub level: Level,
+    pub nodes: usize,
+    pub ranks: usize,
+    pub contracted_nodes: usize,
+    pub created_shortcuts: usize,
+    pub hop_limited_shortcuts: usize,
+    pub settled_limited_shortcuts: usize,
+    pub seconds: f64,
+    /// edges of this level in the bin-file, including shortcuts
+    pub edges: usize,
+    /// shortcuts of this level in the bin-file
+    pub shortcuts: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PhaseReport {
+    pub name: String,
+    pub seconds: f64,
+}
+
+/// statistics of a whole pre-computation
+#[derive(Serialize, Default, Debug)]
+pub struct Report {
+    pub amount_nodes: usize,
+    pub amount_edges: usize,
+    pub amount_shortcuts: usize,
+    pub mlp_levels: Vec<usize>,
+    pub phases: Vec<PhaseReport>,
+    pub levels: Vec<LevelReport>,
+    pub ranks: Vec<RankReport>,
+    /// highest resident memory of the process in kilobytes, only known on linux
+    pub peak_memory_kb: Option<u64>,
+}
+
+impl PhaseReport {
+    pub fn new(name: &str, duration: Duration) -> Self {
+        PhaseReport {
+            name: name.to_string(),
+            seconds: duration.as_secs_f64(),
+        }
+    }
+}
+
+impl Report {
+    pub fn add_phase(&mut self, name: &str, duration: Duration) {
+        self.phases.push(PhaseReport::new(name, duration));
+    }
+
+    /// summarize ranks and the resulting edges per level
+    pub fn add_levels(&mut self, nodes: &[Node], edges: &[Edge]) {
+        // shortcuts created and contracted within a level keep no level in the bin-file,
+        // they belong to the level of the node they skip
+        let edge_level = |edge: &Edge| {
+            edge.level.or_else(|| {
+                edge.contracted_edges
+                    .map(|(prev, _)| nodes[edges[prev].to].level)
+            })
+        };
+        self.levels = (0..=self.mlp_levels.len())
+            .map(|level| {
+                let ranks: Vec<&RankReport> = self
+                    .ranks
+                    .iter()
+                    .filter(|rank| rank.level == level)
+                    .collect();
+                let level_edges: Vec<&Edge> = edges
+                    .iter()
+                    .filter(|edge| edge_level(edge) == Some(level))
+                    .collect();
+                LevelReport {
+                    level,
+                    nodes: nodes.iter().filter(|node| node.level == level).count(),
+                    ranks: ranks.len(),
+                    contracted_nodes: ranks.iter().map(|rank| rank.independent_set).sum(),
+                    created_shortcuts: ranks.iter().map(|rank| rank.shortcuts).sum(),
+                    hop_limited_shortcuts: ranks
+                        .iter()
+                        .map(|rank| rank.hop_limited_shortcuts)
+                        .sum(),
+                    settled_limited_shortcuts: ranks
+                        .iter()
+                        .map(|rank| rank.settled_limited_shortcuts)
+                        .sum(),
+                    seconds: ranks.iter().fold(0.0, |sum, rank| sum + rank.seconds),
+                    edges: level_edges.len(),
+                    shortcuts: level_edges
+                        .iter()
+                        .filter(|edge| edge.contracted_edges.is_some())
+                        .count(),
+                }
+            })
+            .collect();
+        self.amount_shortcuts = edges
+            .iter()
+            .filter(|edge| edge.contracted_edges.is_some())
+            .count();
+    }
+
+    /// json with everything or csv with one line per rank, chosen by the file extension
+    pub fn write_file(&self, file_path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        if file_path.ends_with(".csv") {
+            self.write_csv(&mut writer)?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, self)?;
+        }
+        writer.flush()
+    }
+
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "rank,level,remaining_nodes,independent_set,edges,shortcuts,hop_limited_shortcuts,settled_limited_shortcuts,removed_edges,resulting_edges,seconds"
+        )?;
+        for rank in &self.ranks {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                rank.rank,
+                rank.level,
+                rank.remaining_nodes,
+                rank.independent_set,
+                rank.edges,
+                rank.shortcuts,
+                rank.hop_limited_shortcuts,
+                rank.settled_limited_shortcuts,
+                rank.removed_edges,
+                rank.resulting_edges,
+                rank.seconds
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// peak resident set size from `/proc/self/status`
+pub fn peak_memory_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with("VmHWM:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[test]
+fn levels_and_csv() {
+    let node = |level| Node {
+        level,
+        ..Node::new(0.0, 0.0)
+    };
+    let nodes = vec![node(0), node(0), node(1)];
+    let mut edges = vec![
+        Edge::new(0, 1, vec![1.0]),
+        Edge::new(1, 2, vec![1.0]),
+        Edge::new(0, 2, vec![2.0]),
+    ];
+    edges[0].level = Some(0);
+    edges[1].level = Some(1);
+    edges[2].level = Some(1);
+    edges[2].contracted_edges = Some((0, 1));
+    // shortcut created and contracted on level 0
+    let mut shortcut = Edge::new(0, 2, vec![2.0]);
+    shortcut.contracted_edges = Some((0, 1));
+    edges.push(shortcut);
+    let rank = |rank, level, shortcuts| RankReport {
+        rank,
+        level,
+        remaining_nodes: 0,
+        independent_set: 1,
+        edges: 0,
+        shortcuts,
+        hop_limited_shortcuts: shortcuts,
+        settled_limited_shortcuts: 0,
+        removed_edges: 0,
+        resulting_edges: 0,
+        seconds: 0.5,
+    };
+    let mut report = Report {
+        mlp_levels: vec![2],
+        ranks: vec![rank(0, 0, 1), rank(1, 0, 0)],
+        ..Report::default()
+    };
+
+    report.add_levels(&nodes, &edges);
+
+    assert_eq!(report.amount_shortcuts, 2);
+    assert_eq!(
+        report.levels[0],
+        LevelReport {
+            level: 0,
+            nodes: 2,
+            ranks: 2,
+            contracted_nodes: 2,
+            created_shortcuts: 1,
+            hop_limited_shortcuts: 1,
+            settled_limited_shortcuts: 0,
+            seconds: 1.0,
+            edges: 2,
+            shortcuts: 1,
+        }
+    );
+    assert_eq!(report.levels[1].shortcuts, 1);
+
+    let mut csv = Vec::new();
+    report.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    assert_eq!(csv.lines().nth(1), Some("0,0,0,1,0,1,1,0,0,0,0.5"));
+}
@@ -3,7 +3,7 @@ use std::cmp::Ordering;
 
 use crate::constants::*;
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct Node {
     pub latitude: Angle,
     pub longitude: Angle,
@@ -15,6 +15,20 @@ pub struct Node {
     pub old_id: Option<NodeId>,
 }
 
+impl Node {
+    /// node without rank and partition, as it is read from a graph file
+    pub fn new(latitude: Angle, longitude: Angle) -> Self {
+        Node {
+            latitude,
+            longitude,
+            rank: INVALID_RANK,
+            partition: INVALID_PARTITION,
+            level: INVALID_LEVEL,
+            old_id: None,
+        }
+    }
+}
+
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct Edge {
     pub from: NodeId,
@@ -104,4 +118,26 @@ pub struct BinFile {
     pub grid: Vec<NodeId>,
     pub grid_bounds: GridBounds,
     pub metrics: Vec<String>,
+    /// strongly connected component of each node
+    pub strong_components: Vec<ComponentId>,
+    /// connected component of each node, ignoring edge directions
+    pub weak_components: Vec<ComponentId>,
+    pub edge_orders: EdgeOrders,
+}
+
+/// edge-ids of every node in the order a query type expects them,
+/// grouped by node like `up_offset` and `down_offset`
+#[derive(Serialize, Debug)]
+pub struct EdgeOrder {
+    pub up: Vec<EdgeId>,
+    pub down: Vec<EdgeId>,
+}
+
+/// normal and bidirectional dijkstra share one order
+#[derive(Serialize, Debug)]
+pub struct EdgeOrders {
+    pub normal: EdgeOrder,
+    pub pch: EdgeOrder,
+    pub pcrp: EdgeOrder,
+    pub prp: EdgeOrder,
 }
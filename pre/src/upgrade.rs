@@ -0,0 +1,123 @@
+use super::*;
+use bincode::deserialize_from;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+// layout of the bin-files written before the format version,
+// they lack the components and the edge orders
+#[derive(Deserialize)]
+struct LegacyNode {
+    latitude: Angle,
+    longitude: Angle,
+    rank: Rank,
+    partition: PartitionId,
+}
+
+#[derive(Deserialize)]
+struct LegacyEdge {
+    from: NodeId,
+    to: NodeId,
+    level: Option<Level>,
+    contracted_edges: Option<(EdgeId, EdgeId)>,
+}
+
+#[derive(Deserialize)]
+struct LegacyGridBounds {
+    lat_amount: usize,
+    lat_min: Angle,
+    lat_max: Angle,
+    lng_amount: usize,
+    lng_min: Angle,
+    lng_max: Angle,
+}
+
+#[derive(Deserialize)]
+struct LegacyBinFile {
+    nodes: Vec<LegacyNode>,
+    mlp_levels: Vec<usize>,
+    edges: Vec<LegacyEdge>,
+    edge_costs: Vec<Cost>,
+    up_offset: Vec<EdgeId>,
+    down_offset: Vec<EdgeId>,
+    down_index: Vec<EdgeId>,
+    grid_offset: Vec<GridId>,
+    grid: Vec<NodeId>,
+    grid_bounds: LegacyGridBounds,
+    metrics: Vec<String>,
+}
+
+/// read a bin-file without format version and compute the missing components and edge orders
+pub fn read_legacy_file(filename: &str) -> Result<BinFile, Box<bincode::ErrorKind>> {
+    let mut reader = BufReader::new(File::open(filename)?);
+    from_legacy(&mut reader)
+}
+
+pub fn from_legacy<R: Read>(reader: &mut R) -> Result<BinFile, Box<bincode::ErrorKind>> {
+    let legacy: LegacyBinFile = deserialize_from(reader)?;
+
+    let nodes: Vec<Node> = legacy
+        .nodes
+        .into_iter()
+        .map(|node| Node {
+            rank: node.rank,
+            partition: node.partition,
+            ..Node::new(node.latitude, node.longitude)
+        })
+        .collect();
+    let dim = legacy.edge_costs.len() / legacy.edges.len().max(1);
+    let edges: Vec<Edge> = legacy
+        .edges
+        .into_iter()
+        .zip(legacy.edge_costs.chunks(dim.max(1)))
+        .map(|(edge, cost)| Edge {
+            level: edge.level,
+            contracted_edges: edge.contracted_edges,
+            ..Edge::new(edge.from, edge.to, cost.to_vec())
+        })
+        .collect();
+
+    let strong_components = components::strongly_connected(nodes.len(), &edges, &legacy.up_offset);
+    let weak_components = components::weakly_connected(nodes.len(), &edges);
+    let edge_orders = edge_order::generate_edge_orders(
+        &nodes,
+        &edges,
+        &legacy.up_offset,
+        &legacy.down_offset,
+        &legacy.down_index,
+    );
+
+    Ok(BinFile {
+        nodes,
+        mlp_levels: legacy.mlp_levels,
+        edges,
+        edge_costs: legacy.edge_costs,
+        up_offset: legacy.up_offset,
+        down_offset: legacy.down_offset,
+        down_index: legacy.down_index,
+        grid_offset: legacy.grid_offset,
+        grid: legacy.grid,
+        grid_bounds: GridBounds {
+            lat_amount: legacy.grid_bounds.lat_amount,
+            lat_min: legacy.grid_bounds.lat_min,
+            lat_max: legacy.grid_bounds.lat_max,
+            lng_amount: legacy.grid_bounds.lng_amount,
+            lng_min: legacy.grid_bounds.lng_min,
+            lng_max: legacy.grid_bounds.lng_max,
+        },
+        metrics: legacy.metrics,
+        strong_components,
+        weak_components,
+        edge_orders,
+    })
+}
+
+#[test]
+fn legacy() {
+    let bin_file = pipeline::quadrant_pipeline(4).run().unwrap();
+    let expected = bincode::serialize(&bin_file).unwrap();
+
+    // old bin-files are the current layout up to the metrics
+    let upgraded = from_legacy(&mut expected.as_slice()).unwrap();
+    assert_eq!(bincode::serialize(&upgraded).unwrap(), expected);
+}
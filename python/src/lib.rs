@@ -0,0 +1,304 @@
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use prp_query::query_export::*;
+use prp_query::*;
+
+/// a loaded bin-file with a dijkstra for every query type
+#[pyclass(name = "Graph", unsendable)]
+struct PyGraph {
+    data: WebData,
+    engines: HashMap<QueryType, Box<dyn FindPath<Counter>>>,
+    debug_engines: HashMap<QueryType, Box<dyn FindPath<RealExport>>>,
+}
+
+#[pymethods]
+impl PyGraph {
+    #[new]
+    fn new(file: &str) -> PyResult<Self> {
+        let data = bin_import::load_web_data(file).map_err(|error| {
+            PyIOError::new_err(format!("error while reading bin-file: {}", error))
+        })?;
+        Ok(PyGraph {
+            data,
+            engines: HashMap::new(),
+            debug_engines: HashMap::new(),
+        })
+    }
+
+    #[getter]
+    fn metrics(&self) -> Vec<String> {
+        self.data.metrics.clone()
+    }
+
+    #[getter]
+    fn amount_nodes(&self) -> usize {
+        self.data.nodes.len()
+    }
+
+    #[getter]
+    fn amount_edges(&self) -> usize {
+        self.data.graph.edges.len()
+    }
+
+    /// closest node-id of a coordinate, ValueError if there is no node near it
+    fn snap(&self, latitude: Angle, longitude: Angle) -> PyResult<NodeId> {
+        snap(&self.data, latitude, longitude)
+    }
+
+    /// closest node-ids of arrays of latitudes and longitudes,
+    /// ValueError if there is no node near one of them
+    fn snap_many<'py>(
+        &self,
+        py: Python<'py>,
+        latitudes: PyReadonlyArray1<Angle>,
+        longitudes: PyReadonlyArray1<Angle>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let latitudes = latitudes.as_array();
+        let longitudes = longitudes.as_array();
+        if latitudes.len() != longitudes.len() {
+            return Err(PyValueError::new_err(
+                "latitudes and longitudes differ in length",
+            ));
+        }
+        let node_ids: Vec<NodeId> = latitudes
+            .iter()
+            .zip(longitudes.iter())
+            .map(|(latitude, longitude)| snap(&self.data, *latitude, *longitude))
+            .collect::<PyResult<Vec<NodeId>>>()?;
+        Ok(node_ids.into_pyarray_bound(py).into_any())
+    }
+
+    /// single query, alpha is a list of weights or a dict of metric names.
+    /// with `search_space` the settled nodes and edges are returned as well
+    #[pyo3(signature = (start, end, alpha, query_type = "prp", search_space = false))]
+    fn query<'py>(
+        &mut self,
+        py: Python<'py>,
+        start: NodeId,
+        end: NodeId,
+        alpha: &Bound<'py, PyAny>,
+        query_type: &str,
+        search_space: bool,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let data = &self.data;
+        let query_type = parse_query_type(query_type)?;
+        let alpha = extract_alpha(alpha, &data.metrics)?;
+        check_node(data, start)?;
+        check_node(data, end)?;
+
+        let options = QueryOptions {
+            metric_costs: true,
+            ..QueryOptions::default()
+        };
+        let request = QueryRequest::on(data, start, end, &alpha).with_options(options);
+        let dict = PyDict::new_bound(py);
+        let result = if search_space {
+            let dijkstra = get_engine(&mut self.debug_engines, query_type, data.nodes.len());
+            let result = dijkstra.find_path(&request).map_err(abort_error)?;
+            let export = dijkstra.get_query_export();
+            dict.set_item("heap_pops", export.heap_pops)?;
+            dict.set_item("relaxed_edges", export.relaxed_edges)?;
+            dict.set_item(
+                "visited_nodes",
+                export.visited_nodes.clone().into_pyarray_bound(py),
+            )?;
+            dict.set_item(
+                "visited_edges",
+                export.visited_edges.clone().into_pyarray_bound(py),
+            )?;
+            dict.set_item("meeting_node", export.meeting_node)?;
+            result
+        } else {
+            let dijkstra = get_engine(&mut self.engines, query_type, data.nodes.len());
+            let result = dijkstra.find_path(&request).map_err(abort_error)?;
+            let export = dijkstra.get_query_export();
+            dict.set_item("heap_pops", export.heap_pops)?;
+            dict.set_item("relaxed_edges", export.relaxed_edges)?;
+            result
+        };
+
+        // no path found gives empty arrays and a cost of NaN
+        dict.set_item("found", result.is_some())?;
+        let (path, cost, costs) = match result {
+            Some(result) => (result.path, result.cost, result.metric_costs.unwrap()),
+            None => (Vec::new(), Cost::NAN, vec![Cost::NAN; data.graph.dim]),
+        };
+        let nodes = data.graph.get_path_node_ids(&path);
+        let mut coordinates = Array2::<Angle>::zeros((nodes.len(), 2));
+        for (mut row, node_id) in coordinates.rows_mut().into_iter().zip(&nodes) {
+            row[0] = data.nodes[*node_id].longitude;
+            row[1] = data.nodes[*node_id].latitude;
+        }
+        dict.set_item("cost", cost)?;
+        dict.set_item("costs", costs.into_pyarray_bound(py))?;
+        dict.set_item("path", path.into_pyarray_bound(py))?;
+        dict.set_item("nodes", nodes.into_pyarray_bound(py))?;
+        dict.set_item("coordinates", coordinates.into_pyarray_bound(py))?;
+        Ok(dict)
+    }
+
+    /// one query per row, alphas has one row per query or a single row for all of them.
+    /// returns arrays of cost (NaN without path), costs per metric, found, time in ns,
+    /// heap pops and relaxed edges
+    #[pyo3(signature = (starts, ends, alphas, query_type = "prp"))]
+    fn batch<'py>(
+        &mut self,
+        py: Python<'py>,
+        starts: PyReadonlyArray1<NodeId>,
+        ends: PyReadonlyArray1<NodeId>,
+        alphas: PyReadonlyArray2<f64>,
+        query_type: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let data = &self.data;
+        let query_type = parse_query_type(query_type)?;
+        let starts = starts.as_array();
+        let ends = ends.as_array();
+        let alphas = alphas.as_array();
+        let amount = starts.len();
+        if ends.len() != amount || (alphas.nrows() != amount && alphas.nrows() != 1) {
+            return Err(PyValueError::new_err(
+                "starts, ends and alphas differ in length",
+            ));
+        }
+        // validate all inputs before querying
+        let alphas = alphas
+            .rows()
+            .into_iter()
+            .map(|row| {
+                Alpha::Vector(row.to_vec())
+                    .resolve(&data.metrics)
+                    .map_err(|error| PyValueError::new_err(error.to_string()))
+            })
+            .collect::<PyResult<Vec<Vec<f64>>>>()?;
+        for node_id in starts.iter().chain(ends.iter()) {
+            check_node(data, *node_id)?;
+        }
+
+        let options = QueryOptions {
+            metric_costs: true,
+            unpack_path: false,
+            ..QueryOptions::default()
+        };
+        let dijkstra = get_engine(&mut self.engines, query_type, data.nodes.len());
+        let mut cost = Vec::with_capacity(amount);
+        let mut costs = Array2::<Cost>::from_elem((amount, data.graph.dim), Cost::NAN);
+        let mut found = Vec::with_capacity(amount);
+        let mut time = Vec::with_capacity(amount);
+        let mut heap_pops = Vec::with_capacity(amount);
+        let mut relaxed_edges = Vec::with_capacity(amount);
+        // other python threads keep running during the queries
+        py.allow_threads(|| -> Result<(), AbortReason> {
+            for (index, (start, end)) in starts.iter().zip(ends.iter()).enumerate() {
+                let alpha = &alphas[if alphas.len() == 1 { 0 } else { index }];
+                let request =
+                    QueryRequest::on(data, *start, *end, alpha).with_options(options.clone());
+                let query_time = Instant::now();
+                let result = dijkstra.find_path(&request)?;
+                time.push(query_time.elapsed().as_nanos() as u64);
+                let export = dijkstra.get_query_export();
+                heap_pops.push(export.heap_pops);
+                relaxed_edges.push(export.relaxed_edges);
+                found.push(result.is_some());
+                match result {
+                    Some(result) => {
+                        cost.push(result.cost);
+                        for (target, metric_cost) in costs
+                            .row_mut(index)
+                            .iter_mut()
+                            .zip(result.metric_costs.unwrap())
+                        {
+                            *target = metric_cost;
+                        }
+                    }
+                    None => cost.push(Cost::NAN),
+                }
+            }
+            Ok(())
+        })
+        .map_err(abort_error)?;
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("cost", cost.into_pyarray_bound(py))?;
+        dict.set_item("costs", costs.into_pyarray_bound(py))?;
+        dict.set_item("found", found.into_pyarray_bound(py))?;
+        dict.set_item("time", time.into_pyarray_bound(py))?;
+        dict.set_item("heap_pops", heap_pops.into_pyarray_bound(py))?;
+        dict.set_item("relaxed_edges", relaxed_edges.into_pyarray_bound(py))?;
+        Ok(dict)
+    }
+}
+
+fn snap(data: &WebData, latitude: Angle, longitude: Angle) -> PyResult<NodeId> {
+    let node_id = grid::get_closest_point(
+        Location {
+            latitude,
+            longitude,
+        },
+        &data.nodes,
+        &data.grid,
+        &data.grid_offset,
+        &data.grid_bounds,
+    );
+    if node_id == INVALID_NODE {
+        return Err(PyValueError::new_err(format!(
+            "no node near {},{}",
+            latitude, longitude
+        )));
+    }
+    Ok(node_id)
+}
+
+fn check_node(data: &WebData, node_id: NodeId) -> PyResult<()> {
+    if node_id < data.nodes.len() {
+        Ok(())
+    } else {
+        Err(PyValueError::new_err(format!(
+            "node-id {} is not in the graph",
+            node_id
+        )))
+    }
+}
+
+fn parse_query_type(query_type: &str) -> PyResult<QueryType> {
+    query_type
+        .parse()
+        .map_err(|_| PyValueError::new_err(format!("unknown query type '{}'", query_type)))
+}
+
+/// list or array of weights, or dict from metric name to weight
+fn extract_alpha(alpha: &Bound<'_, PyAny>, metrics: &[String]) -> PyResult<Vec<f64>> {
+    let alpha = match alpha.extract::<BTreeMap<String, f64>>() {
+        Ok(named) => Alpha::Named(named),
+        Err(_) => Alpha::Vector(alpha.extract::<Vec<f64>>()?),
+    };
+    alpha
+        .resolve(metrics)
+        .map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+fn abort_error(reason: AbortReason) -> PyErr {
+    PyRuntimeError::new_err(format!("query aborted: {}", reason))
+}
+
+/// dijkstras are created on first use and reused afterwards
+fn get_engine<E: 'static + Export>(
+    engines: &mut HashMap<QueryType, Box<dyn FindPath<E>>>,
+    query_type: QueryType,
+    amount_nodes: usize,
+) -> &mut Box<dyn FindPath<E>> {
+    engines
+        .entry(query_type)
+        .or_insert_with(|| dijkstra::get(query_type, amount_nodes, E::new()))
+}
+
+#[pymodule]
+fn prp(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGraph>()?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+"""needs the bindings installed via `maturin develop` and cargo to build the bin-file"""
+
+import pathlib
+import subprocess
+
+import numpy as np
+import pytest
+
+import prp
+
+REPOSITORY = pathlib.Path(__file__).resolve().parents[2]
+SIZE = 3
+
+
+def write_grid(fmi_file):
+    """SIZE x SIZE grid with edges in both directions, distance 1 and time 2 each"""
+    nodes = [(y * SIZE + x, 48.0 + y * 0.01, 9.0 + x * 0.01) for y in range(SIZE) for x in range(SIZE)]
+    edges = []
+    for node_id, _, _ in nodes:
+        x, y = node_id % SIZE, node_id // SIZE
+        for neighbor in ([node_id + 1] if x + 1 < SIZE else []) + ([node_id + SIZE] if y + 1 < SIZE else []):
+            edges += [(node_id, neighbor), (neighbor, node_id)]
+    lines = ["# metrics: distance, time", "", "2", str(len(nodes)), str(len(edges))]
+    lines += [f"{node_id} {node_id} {latitude} {longitude} 0" for node_id, latitude, longitude in nodes]
+    lines += [f"{source} {target} 1 2" for source, target in edges]
+    fmi_file.write_text("\n".join(lines) + "\n")
+
+
+@pytest.fixture(scope="module")
+def graph(tmp_path_factory):
+    directory = tmp_path_factory.mktemp("graph")
+    write_grid(directory / "grid.fmi")
+    subprocess.run(
+        ["cargo", "run", "--release", "--bin", "prp_pre", "--",
+         "-f", directory / "grid.fmi", "-p", "1.0", "-o", directory / "grid.bin"],
+        cwd=REPOSITORY,
+        check=True,
+    )
+    return prp.Graph(str(directory / "grid.bin"))
+
+
+def test_query(graph):
+    assert graph.metrics == ["distance", "time"]
+    assert graph.amount_nodes == SIZE * SIZE
+
+    # prp_pre renumbers the nodes, so the corners are found by their coordinates
+    start = graph.snap(48.0, 9.0)
+    end, = graph.snap_many(np.array([48.02]), np.array([9.02]))
+    assert start != end
+
+    route = graph.query(start, end, {"distance": 1}, query_type="pch")
+    assert route["found"]
+    assert route["cost"] == 4
+    np.testing.assert_array_equal(route["costs"], [4, 8])
+    assert route["nodes"][0] == start and route["nodes"][-1] == end
+
+    batch = graph.batch(np.array([start, end]), np.array([end, start]), np.array([[0.0, 1.0]]), query_type="pch")
+    np.testing.assert_array_equal(batch["cost"], [8, 8])
+
+
+def test_invalid_node(graph):
+    with pytest.raises(ValueError):
+        graph.query(0, SIZE * SIZE, [1.0, 0.0])
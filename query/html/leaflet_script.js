@@ -1,14 +1,9 @@
-let map = L.map('map', {
-    maxBounds: [
-        [47.1, 5.7], // Southwest coordinates
-        [55.2, 16.9] // Northeast coordinates
-    ],
-}).setView([51.15, 11.3], 6);
+// view gets fitted to the bounds of the loaded graph
+let map = L.map('map').setView([0, 0], 2);
 
 L.tileLayer('https://{s}.tile.openstreetmap.org/{z}/{x}/{y}.png', {
     attribution: '&copy; <a href="https://www.openstreetmap.org/copyright">OpenStreetMap</a> contributors',
     maxZoom: 18,
-    minZoom: 6,
     id: 'mapbox.streets',
 }).addTo(map);
 map.on('click', onMapClick);
@@ -19,10 +14,21 @@ let endPoint;
 let endMarker;
 let tmpMarker;
 let last_path;
+let last_search_space;
+let elicitation_layers = [];
+const elicitation_colors = ["#2b5696", "#e67e22"];
 let xhr = new XMLHttpRequest();
 let metrics;
+// empty for the default graph, otherwise "graphs/<name>/"
+let graph_prefix = "";
+// stored preference profiles of the current user
+let profiles = [];
 
+getGraphs();
+getInfo();
 getMetrics();
+document.getElementById("profile-user").value = localStorage.getItem("profile-user") || "";
+getProfiles();
 
 function onMapClick(e) {
     if (tmpMarker) {
@@ -33,9 +39,72 @@ function onMapClick(e) {
     tmpMarker.bindPopup("<button class='set-point set-start' onclick='setStart()''>Set Start</button><button class='set-point set-end' onclick='setEnd()''>Set End</button>").openPopup();
 }
 
+function getGraphs() {
+    let xhr = new XMLHttpRequest();
+    xhr.open("GET", window.location.href + "graphs", true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            let graphs = JSON.parse(xhr.responseText);
+
+            setGraphSelection(graphs);
+        }
+    };
+    xhr.send();
+}
+
+function setGraphSelection(graphs) {
+    // only offer a selection if there is something to select
+    if (graphs.length < 2) {
+        return;
+    }
+    let options = "";
+    graphs.forEach(function (graph, index) {
+        options += `<option value="${index === 0 ? "" : "graphs/" + graph.name + "/"}">${graph.name}</option>`;
+    });
+    let select = document.getElementById("graph-select");
+    select.innerHTML = options;
+    document.getElementById("graph-container").style.display = "block";
+}
+
+function selectGraph() {
+    graph_prefix = document.getElementById("graph-select").value;
+    getInfo();
+    getMetrics();
+}
+
+function getInfo() {
+    let xhr = new XMLHttpRequest();
+    xhr.open("GET", window.location.href + graph_prefix + "info", true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            let info = JSON.parse(xhr.responseText);
+
+            fitGraphBounds(info.grid_bounds);
+        } else if (xhr.readyState === 4) {
+            show_invalid_request();
+        }
+    };
+    xhr.send();
+}
+
+function fitGraphBounds(grid_bounds) {
+    let bounds = L.latLngBounds(
+        [grid_bounds.lat_min, grid_bounds.lng_min], // Southwest coordinates
+        [grid_bounds.lat_max, grid_bounds.lng_max] // Northeast coordinates
+    );
+    let padded_bounds = bounds.pad(0.1);
+    map.setMaxBounds(padded_bounds);
+    map.fitBounds(bounds);
+    map.setMinZoom(map.getBoundsZoom(padded_bounds));
+}
+
 function getMetrics() {
     let xhr = new XMLHttpRequest();
-    xhr.open("GET", window.location.href + "metrics", true);
+    xhr.open("GET", window.location.href + graph_prefix + "metrics", true);
     xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
 
     xhr.onreadystatechange = function () {
@@ -60,6 +129,105 @@ function setSlider(metrics) {
                             `;
     });
     document.getElementById("slidercontainer").innerHTML = slide_container;
+    // keep the selected profile instead of the default weights
+    applyProfile();
+}
+
+function setUser() {
+    localStorage.setItem("profile-user", document.getElementById("profile-user").value);
+    getProfiles();
+}
+
+function getProfiles() {
+    let user = document.getElementById("profile-user").value;
+    if (user === "") {
+        profiles = [];
+        setProfileSelection();
+        return;
+    }
+    let xhr = new XMLHttpRequest();
+    xhr.open("GET", window.location.href + "profiles?user=" + encodeURIComponent(user), true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            profiles = JSON.parse(xhr.responseText);
+            setProfileSelection();
+        } else if (xhr.readyState === 4) {
+            show_invalid_request();
+        }
+    };
+    xhr.send();
+}
+
+function setProfileSelection() {
+    let select = document.getElementById("profile-select");
+    let selected = select.value || localStorage.getItem("profile-id") || "";
+    let options = `<option value="">-</option>`;
+    profiles.forEach(function (profile) {
+        let stale = profile.stale.length > 0 ? " (stale)" : "";
+        options += `<option value="${profile.id}">${profile.name}${stale}</option>`;
+    });
+    select.innerHTML = options;
+    if (profiles.some(profile => String(profile.id) === selected)) {
+        select.value = selected;
+        applyProfile();
+    }
+}
+
+function selectProfile() {
+    localStorage.setItem("profile-id", document.getElementById("profile-select").value);
+    applyProfile();
+    query();
+}
+
+function applyProfile() {
+    let id = document.getElementById("profile-select").value;
+    let profile = profiles.find(profile => String(profile.id) === id);
+    if (typeof profile === 'undefined' || typeof metrics === 'undefined') {
+        return;
+    }
+    metrics.forEach(function (metric, index) {
+        let slider = document.getElementById("slider-" + index);
+        if (slider && metric in profile.alpha) {
+            slider.value = profile.alpha[metric];
+        }
+    });
+}
+
+function saveProfile() {
+    let user = document.getElementById("profile-user").value;
+    if (user === "" || typeof metrics === 'undefined') {
+        show_invalid_request();
+        return;
+    }
+    let id = document.getElementById("profile-select").value;
+    let current = profiles.find(profile => String(profile.id) === id);
+    let name = prompt("Profile name", current ? current.name : "");
+    if (!name) {
+        return;
+    }
+    let alpha = {};
+    get_alpha_vector().forEach(function (value, index) {
+        alpha[metrics[index]] = value;
+    });
+    // overwrite the selected profile, if the name did not change
+    let update = current && current.name === name;
+    let xhr = new XMLHttpRequest();
+    xhr.open(update ? "PUT" : "POST", window.location.href + "profiles" + (update ? "/" + current.id : ""), true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            let profile = JSON.parse(xhr.responseText);
+            localStorage.setItem("profile-id", profile.id);
+            document.getElementById("profile-select").value = "";
+            getProfiles();
+        } else if (xhr.readyState === 4) {
+            show_invalid_request();
+        }
+    };
+    xhr.send(JSON.stringify({ "user": user, "name": name, "alpha": alpha }));
 }
 
 function setStart() {
@@ -124,6 +292,10 @@ function query() {
     if (typeof last_path === 'object') {
         map.removeLayer(last_path);
     }
+    if (typeof last_search_space === 'object') {
+        map.removeLayer(last_search_space);
+        last_search_space = undefined;
+    }
 
     if (typeof startPoint === 'undefined' || typeof endPoint === 'undefined') {
         show_select_start_and_end();
@@ -131,7 +303,7 @@ function query() {
     }
 
     let xhr = new XMLHttpRequest();
-    xhr.open("POST", window.location.href + "dijkstra", true);
+    xhr.open("POST", window.location.href + graph_prefix + "dijkstra", true);
     xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
 
     xhr.onreadystatechange = function () {
@@ -139,7 +311,7 @@ function query() {
             let json = JSON.parse(xhr.responseText);
             if (json.path != "") {
                 printPath(json);
-                show_result(json.features[0].properties.cost);
+                show_result(json.features[0].properties.cost, json.features[0].properties.tradeoffs);
             } else {
                 show_no_path_found();
             }
@@ -162,6 +334,7 @@ function query() {
                 },
                 "properties": {
                     "alpha": alpha_vector,
+                    "compare": document.getElementById("compare-toggle").checked,
                 },
             },
             {
@@ -175,6 +348,7 @@ function query() {
                 },
                 "properties": {
                     "alpha": alpha_vector,
+                    "compare": document.getElementById("compare-toggle").checked,
                 },
             }
         ]
@@ -182,6 +356,124 @@ function query() {
     let data = JSON.stringify(body);
     // console.log("request: " + data);
     xhr.send(data);
+
+    if (document.getElementById("search-space-toggle").checked) {
+        getSearchSpace(data);
+    }
+}
+
+function getSearchSpace(data) {
+    let xhr = new XMLHttpRequest();
+    xhr.open("POST", window.location.href + graph_prefix + "debug/search-space", true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            let search_space = JSON.parse(xhr.responseText);
+            printSearchSpace(search_space);
+        } else if (xhr.readyState === 4) {
+            show_invalid_request();
+        }
+    };
+    xhr.send(data);
+}
+
+function printSearchSpace(search_space) {
+    // edges are colored by direction and level, the path stays on top
+    let style = function (feature) {
+        return { color: feature.properties.color, weight: 2, opacity: 0.8 };
+    };
+    last_search_space = L.layerGroup([
+        L.geoJSON(search_space.forward, { style: style }),
+        L.geoJSON(search_space.backward, { style: style }),
+    ]);
+    if (search_space.meeting_node) {
+        last_search_space.addLayer(L.circleMarker(
+            [search_space.meeting_node[1], search_space.meeting_node[0]],
+            { radius: 6, color: "#000" }
+        ).bindPopup(`heap pops: ${search_space.heap_pops}<br>relaxed edges: ${search_space.relaxed_edges}`));
+    }
+    map.addLayer(last_search_space);
+    if (typeof last_path === 'object') {
+        last_path.bringToFront();
+    }
+}
+
+function getPointsBody() {
+    let point = function (latLng) {
+        return {
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [latLng.lng, latLng.lat]
+            },
+            "properties": null,
+        };
+    };
+    return JSON.stringify({
+        "type": "FeatureCollection",
+        "features": [point(startPoint), point(endPoint)]
+    });
+}
+
+function startElicitation() {
+    if (typeof startPoint === 'undefined' || typeof endPoint === 'undefined') {
+        show_select_start_and_end();
+        return;
+    }
+    sendElicitation(graph_prefix + "elicitation", getPointsBody());
+}
+
+function chooseRoute(session_id, choice) {
+    sendElicitation("elicitation/" + session_id, JSON.stringify({ "choice": choice }));
+}
+
+function sendElicitation(url, data) {
+    let xhr = new XMLHttpRequest();
+    xhr.open("POST", window.location.href + url, true);
+    xhr.setRequestHeader("Content-type", "application/json;charset=UTF-8");
+
+    xhr.onreadystatechange = function () {
+        if (xhr.readyState === 4 && xhr.status === 200) {
+            showElicitation(JSON.parse(xhr.responseText));
+        } else if (xhr.readyState === 4) {
+            show_invalid_request();
+        }
+    };
+    xhr.send(data);
+}
+
+function showElicitation(response) {
+    elicitation_layers.forEach(layer => map.removeLayer(layer));
+    elicitation_layers = [];
+    let container = document.getElementById("elicitation-container");
+
+    if (response.done) {
+        // take over the learned preference, it can be stored as profile
+        container.style.display = "none";
+        metrics.forEach(function (metric, index) {
+            document.getElementById("slider-" + index).value = response.profile[metric];
+        });
+        query();
+        return;
+    }
+
+    let buttons = "";
+    response.options.forEach(function (option, index) {
+        let costs = Object.entries(option.costs)
+            .map(([metric, cost]) => `${metric}: ${cost.toFixed(2)}`)
+            .join("<br>");
+        let layer = L.polyline(option.coordinates.map(c => [c[1], c[0]]), {
+            color: elicitation_colors[index],
+            weight: 5,
+            opacity: 0.7,
+        }).bindPopup(costs);
+        elicitation_layers.push(layer);
+        map.addLayer(layer);
+        buttons += `<button class="info-button" style="background-color:${elicitation_colors[index]}" onclick="chooseRoute(${response.session_id}, ${index})">${costs}</button>`;
+    });
+    document.getElementById("elicitation-options").innerHTML = buttons;
+    container.style.display = "block";
 }
 
 function printPath(path) {
@@ -224,9 +516,14 @@ function hide_select_start_and_end() {
     }
 }
 
-function show_result(costs) {
+function show_result(costs, tradeoffs) {
     let tmp = document.getElementById("result")
     tmp.innerHTML = "costs: " + costs;
+    // e.g. "vs. fastest time: +7% time, −40% ascent"
+    (tradeoffs || []).forEach(function (tradeoff) {
+        let summary = tradeoff.same_route ? "same route" : tradeoff.summary;
+        tmp.innerHTML += `<br>vs. best ${tradeoff.optimized_metric}: ${summary}`;
+    });
     tmp.style.display = "block";
 }
 
@@ -0,0 +1,200 @@
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// weights of the metrics, either positional or keyed by metric name.
+/// named weights are independent of the metric order in the graph-file
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Alpha {
+    Vector(Vec<f64>),
+    /// metrics without weight default to 0
+    Named(BTreeMap<String, f64>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlphaError {
+    WrongLength { expected: usize, found: usize },
+    UnknownMetric { metric: String, known: Vec<String> },
+    InvalidWeight { metric: String, weight: f64 },
+    AllZero,
+}
+
+impl fmt::Display for AlphaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlphaError::WrongLength { expected, found } => write!(
+                f,
+                "alpha has {} weights, but the graph has {} metrics",
+                found, expected
+            ),
+            AlphaError::UnknownMetric { metric, known } => write!(
+                f,
+                "unknown metric '{}' in alpha, known metrics are {:?}",
+                metric, known
+            ),
+            AlphaError::InvalidWeight { metric, weight } => write!(
+                f,
+                "weight {} of '{}' has to be a non-negative number",
+                weight, metric
+            ),
+            AlphaError::AllZero => write!(f, "at least one weight of alpha has to be positive"),
+        }
+    }
+}
+
+impl std::error::Error for AlphaError {}
+
+// untagged enums buffer their input, which breaks numbers with serde_json's arbitrary_precision
+impl<'de> Deserialize<'de> for Alpha {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AlphaVisitor;
+
+        impl<'de> Visitor<'de> for AlphaVisitor {
+            type Value = Alpha;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a list of weights or a map of metric names to weights")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Alpha, A::Error> {
+                let mut weights = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(weight) = seq.next_element()? {
+                    weights.push(weight);
+                }
+                Ok(Alpha::Vector(weights))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Alpha, A::Error> {
+                let mut weights = BTreeMap::new();
+                while let Some((metric, weight)) = map.next_entry::<String, f64>()? {
+                    if weights.insert(metric.clone(), weight).is_some() {
+                        return Err(de::Error::custom(format!(
+                            "duplicate metric '{}' in alpha",
+                            metric
+                        )));
+                    }
+                }
+                Ok(Alpha::Named(weights))
+            }
+        }
+
+        deserializer.deserialize_any(AlphaVisitor)
+    }
+}
+
+impl From<Vec<f64>> for Alpha {
+    fn from(alpha: Vec<f64>) -> Self {
+        Alpha::Vector(alpha)
+    }
+}
+
+impl Alpha {
+    /// validated weights in the metric order of a graph
+    pub fn resolve(&self, metrics: &[String]) -> Result<Vec<f64>, AlphaError> {
+        let weights = match self {
+            Alpha::Vector(weights) => {
+                if weights.len() != metrics.len() {
+                    return Err(AlphaError::WrongLength {
+                        expected: metrics.len(),
+                        found: weights.len(),
+                    });
+                }
+                weights.clone()
+            }
+            Alpha::Named(named) => {
+                if let Some(metric) = named.keys().find(|metric| !metrics.contains(metric)) {
+                    return Err(AlphaError::UnknownMetric {
+                        metric: metric.clone(),
+                        known: metrics.to_vec(),
+                    });
+                }
+                metrics
+                    .iter()
+                    .map(|metric| named.get(metric).copied().unwrap_or(0.0))
+                    .collect()
+            }
+        };
+        for (metric, weight) in metrics.iter().zip(&weights) {
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(AlphaError::InvalidWeight {
+                    metric: metric.clone(),
+                    weight: *weight,
+                });
+            }
+        }
+        if weights.iter().all(|weight| *weight == 0.0) {
+            return Err(AlphaError::AllZero);
+        }
+        Ok(weights)
+    }
+
+    /// like `resolve`, but scaled onto the simplex if requested
+    pub fn resolve_normalized(
+        &self,
+        metrics: &[String],
+        normalize: bool,
+    ) -> Result<Vec<f64>, AlphaError> {
+        let mut weights = self.resolve(metrics)?;
+        if normalize {
+            normalize_to_simplex(&mut weights);
+        }
+        Ok(weights)
+    }
+}
+
+/// scale weights to sum up to 1, only the ratio of the weights changes the route
+pub fn normalize_to_simplex(weights: &mut [f64]) {
+    let sum: f64 = weights.iter().sum();
+    if sum > 0.0 {
+        for weight in weights.iter_mut() {
+            *weight /= sum;
+        }
+    }
+}
+
+#[test]
+fn named_alpha() {
+    let metrics = vec![
+        "distance".to_string(),
+        "time".to_string(),
+        "height".to_string(),
+    ];
+    let alpha: Alpha = serde_json::from_str(r#"{ "time": 3.0, "distance": 1.0 }"#).unwrap();
+    assert_eq!(alpha.resolve(&metrics), Ok(vec![1.0, 3.0, 0.0]));
+    assert_eq!(
+        alpha.resolve_normalized(&metrics, true),
+        Ok(vec![0.25, 0.75, 0.0])
+    );
+
+    let alpha: Alpha = serde_json::from_str("[0.5, 0.5, 0.0]").unwrap();
+    assert_eq!(alpha, Alpha::Vector(vec![0.5, 0.5, 0.0]));
+    assert_eq!(alpha.resolve(&metrics), Ok(vec![0.5, 0.5, 0.0]));
+}
+
+#[test]
+fn invalid_alpha() {
+    let metrics = vec!["distance".to_string(), "time".to_string()];
+    let named = |json: &str| serde_json::from_str::<Alpha>(json).unwrap();
+
+    assert!(matches!(
+        named(r#"{ "speed": 1.0 }"#).resolve(&metrics),
+        Err(AlphaError::UnknownMetric { .. })
+    ));
+    assert!(matches!(
+        named(r#"{ "time": -1.0 }"#).resolve(&metrics),
+        Err(AlphaError::InvalidWeight { .. })
+    ));
+    assert_eq!(
+        named(r#"{ "time": 0.0 }"#).resolve(&metrics),
+        Err(AlphaError::AllZero)
+    );
+    assert_eq!(
+        Alpha::from(vec![1.0]).resolve(&metrics),
+        Err(AlphaError::WrongLength {
+            expected: 2,
+            found: 1
+        })
+    );
+}
@@ -2,6 +2,7 @@
 use log::warn;
 use serde::Serialize;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::process;
 use std::str::FromStr;
@@ -16,6 +17,8 @@ enum Vals {
     Count,
     Export,
     Check,
+    Phases,
+    Bench,
 }
 
 impl FromStr for Vals {
@@ -26,6 +29,8 @@ impl FromStr for Vals {
             "count" => Ok(Vals::Count),
             "export" => Ok(Vals::Export),
             "check" => Ok(Vals::Check),
+            "phases" => Ok(Vals::Phases),
+            "bench" => Ok(Vals::Bench),
             _ => Err("no match"),
         }
     }
@@ -36,6 +41,29 @@ struct TimeExport {
     id: usize,
     time: u128,
 }
+/// nanoseconds spent in each phase of a query
+#[derive(Debug, Serialize)]
+struct PhaseExport {
+    id: usize,
+    #[serde(flatten)]
+    phases: BTreeMap<Phase, u128>,
+    total: u128,
+}
+/// throughput over repeated rounds of all queries, latencies in microseconds
+#[derive(Debug, Serialize)]
+struct BenchExport {
+    queries: usize,
+    rounds: usize,
+    mean: f64,
+    median: f64,
+    p95: f64,
+    queries_per_second: f64,
+    relaxed_edges_per_second: f64,
+    /// milliseconds for visiting the up and down edges of every node
+    adjacency_iterate: f64,
+    /// same, but collecting the edge-ids of each node first
+    adjacency_collect: f64,
+}
 #[derive(Debug, Serialize)]
 struct CounterExport {
     id: usize,
@@ -51,36 +79,15 @@ pub struct GrapInfo {
 }
 
 fn main() {
-    let (fmi_file, eval_file, eval_type, query_type, export_graph_info, export_path) =
+    let (fmi_file, eval_file, eval_type, query_type, export_graph_info, export_path, rounds) =
         get_arguments();
     // read binfile
-    let mut data: BinFile = match bin_import::read_file(&fmi_file) {
+    let data: WebData = match bin_import::load_web_data(&fmi_file) {
         Ok(result) => result,
         Err(error) => panic!("error while reading bin-file: {:?}", error),
     };
 
     let amount_nodes = data.nodes.len();
-    let dim = data.edge_costs.len() / data.edges.len();
-
-    sort_edges::sort_edges(query_type, &mut data);
-
-    let graph = Graph::new(
-        data.edges,
-        data.edge_costs,
-        data.up_offset,
-        data.down_offset,
-        data.down_index,
-        dim,
-    );
-    let data = WebData {
-        nodes: data.nodes,
-        mlp_levels: data.mlp_levels,
-        graph,
-        grid_offset: data.grid_offset,
-        grid: data.grid,
-        grid_bounds: data.grid_bounds,
-        metrics: data.metrics,
-    };
 
     // if graph infos are needed otherwise normal evaluation
     if export_graph_info {
@@ -88,10 +95,9 @@ fn main() {
         //iterate over all nodes
         for node in 0..data.nodes.len() {
             // extract edges of one node
-            let subvector =
-                &data.graph.edges[data.graph.up_offset[node]..data.graph.up_offset[node + 1]];
             let mut edge_counter = 0;
-            for edge in subvector {
+            for edge_id in data.graph.get_ordered_up_edge_ids(query_type, node) {
+                let edge = data.graph.get_edge(edge_id);
                 // count for every query algo differently
                 match query_type {
                     QueryType::Normal | QueryType::Bi => {
@@ -176,23 +182,160 @@ fn main() {
         }
     }
 
+    // alphas in the metric order of the graph
+    let alphas: Vec<Vec<f64>> = eval
+        .iter()
+        .map(|query| {
+            match query
+                .alpha
+                .resolve_normalized(&data.metrics, query.normalize)
+            {
+                Ok(alpha) => alpha,
+                Err(error) => panic!("invalid alpha of evaluation-point {}: {}", query.id, error),
+            }
+        })
+        .collect();
+
     println!("precalculation done. evaluating now...");
 
     match eval_type {
+        Some(Vals::Phases) => {
+            let mut dijkstra =
+                prp_query::dijkstra::get(query_type, amount_nodes, PhaseTimer::new());
+            let mut export_list: Vec<PhaseExport> = Vec::with_capacity(eval.len());
+
+            for (query, alpha) in eval.iter().zip(&alphas) {
+                let total_time = Instant::now();
+                // snapping again, so given node-ids are timed as well
+                let snap_time = Instant::now();
+                for position in &[&query.start_pos, &query.end_pos] {
+                    grid::get_closest_point(
+                        (*position).clone(),
+                        &data.nodes,
+                        &data.grid,
+                        &data.grid_offset,
+                        &data.grid_bounds,
+                    );
+                }
+                let snap_time = snap_time.elapsed();
+                let result = dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
+                let geometry_time = Instant::now();
+                if let Some(result) = result {
+                    grid::get_coordinates(data.graph.get_path_node_ids(&result.path), &data.nodes);
+                }
+                let geometry_time = geometry_time.elapsed();
+                let total_time = total_time.elapsed();
+
+                // the search itself reports the other phases
+                let timer = dijkstra.get_query_export_mut();
+                timer.phase_finished(Phase::Snap, snap_time);
+                timer.phase_finished(Phase::Geometry, geometry_time);
+                let phases = Phase::ALL
+                    .iter()
+                    .map(|phase| (*phase, timer.get(*phase).as_nanos()))
+                    .collect();
+                export_list.push(PhaseExport {
+                    id: query.id,
+                    phases,
+                    total: total_time.as_nanos(),
+                });
+            }
+
+            print_phase_table(&export_list);
+
+            //export
+            let output =
+                serde_json::to_string_pretty(&serde_json::to_value(export_list).unwrap()).unwrap();
+
+            match export_path {
+                Some(path) => match export::write_file(&path, &output) {
+                    Ok(_) => println!("exported succesfully"),
+                    Err(err) => println!("error while exporting {:?}", err),
+                },
+                None => println!("{}", output),
+            }
+        }
+        Some(Vals::Bench) => {
+            let mut dijkstra = prp_query::dijkstra::get(query_type, amount_nodes, Counter::new());
+            let requests: Vec<QueryRequest> = eval
+                .iter()
+                .zip(&alphas)
+                .map(|(query, alpha)| {
+                    QueryRequest::on(&data, query.start_id.unwrap(), query.end_id.unwrap(), alpha)
+                })
+                .collect();
+
+            // warm up caches and the allocations of the dijkstra
+            for request in &requests {
+                dijkstra.find_path(request).unwrap();
+            }
+
+            let mut latencies: Vec<u128> = Vec::with_capacity(requests.len() * rounds);
+            let mut relaxed_edges = 0;
+            let bench_time = Instant::now();
+            for _ in 0..rounds {
+                for request in &requests {
+                    let query_time = Instant::now();
+                    dijkstra.find_path(request).unwrap();
+                    latencies.push(query_time.elapsed().as_nanos());
+                    relaxed_edges += dijkstra.get_query_export().relaxed_edges;
+                }
+            }
+            let seconds = bench_time.elapsed().as_secs_f64();
+            latencies.sort_unstable();
+
+            let (adjacency_iterate, adjacency_collect) = bench_adjacency(&data.graph);
+            let percentile = |p: usize| match latencies.len() {
+                0 => 0.0,
+                len => latencies[(len - 1) * p / 100] as f64 / 1000.0,
+            };
+            let bench = BenchExport {
+                queries: requests.len(),
+                rounds,
+                mean: latencies.iter().sum::<u128>() as f64
+                    / latencies.len().max(1) as f64
+                    / 1000.0,
+                median: percentile(50),
+                p95: percentile(95),
+                queries_per_second: latencies.len() as f64 / seconds,
+                relaxed_edges_per_second: relaxed_edges as f64 / seconds,
+                adjacency_iterate,
+                adjacency_collect,
+            };
+
+            //export
+            let output =
+                serde_json::to_string_pretty(&serde_json::to_value(bench).unwrap()).unwrap();
+
+            match export_path {
+                Some(path) => match export::write_file(&path, &output) {
+                    Ok(_) => println!("exported succesfully"),
+                    Err(err) => println!("error while exporting {:?}", err),
+                },
+                None => println!("{}", output),
+            }
+        }
         Some(Vals::Time) => {
             let mut dijkstra = prp_query::dijkstra::get(query_type, amount_nodes, NoOp::new());
             let mut export_list: Vec<TimeExport> = Vec::with_capacity(eval.len());
 
-            for query in &eval {
+            for (query, alpha) in eval.iter().zip(&alphas) {
                 let dijkstra_time = Instant::now();
-                dijkstra.find_path(
-                    query.start_id.unwrap(),
-                    query.end_id.unwrap(),
-                    query.alpha.clone(),
-                    &data.graph,
-                    &data.nodes,
-                    &data.mlp_levels,
-                );
+                dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
                 export_list.push(TimeExport {
                     id: query.id,
                     time: dijkstra_time.elapsed().as_nanos(),
@@ -215,15 +358,15 @@ fn main() {
             let mut dijkstra = prp_query::dijkstra::get(query_type, amount_nodes, Counter::new());
             let mut export_list: Vec<CounterExport> = Vec::with_capacity(eval.len());
 
-            for query in &eval {
-                let _result = dijkstra.find_path(
-                    query.start_id.unwrap(),
-                    query.end_id.unwrap(),
-                    query.alpha.clone(),
-                    &data.graph,
-                    &data.nodes,
-                    &data.mlp_levels,
-                );
+            for (query, alpha) in eval.iter().zip(&alphas) {
+                let _result = dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
                 export_list.push(CounterExport {
                     id: query.id,
                     heap_pops: dijkstra.get_query_export().heap_pops,
@@ -250,16 +393,16 @@ fn main() {
             let level_heights =
                 mlp_helper::calculate_levels(&data.nodes, &data.graph, &data.mlp_levels);
 
-            for query in &eval {
-                let result = dijkstra.find_path(
-                    query.start_id.unwrap(),
-                    query.end_id.unwrap(),
-                    query.alpha.clone(),
-                    &data.graph,
-                    &data.nodes,
-                    &data.mlp_levels,
-                );
-                let path = result.unwrap_or((vec![], 0.0));
+            for (query, alpha) in eval.iter().zip(&alphas) {
+                let result = dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
+                let path = result.map(|result| result.path).unwrap_or_default();
 
                 //export
                 match export_path {
@@ -270,7 +413,7 @@ fn main() {
                             query.end_id.unwrap(),
                             dijkstra.get_query_export().meeting_node,
                             &dijkstra.get_query_export().visited_nodes,
-                            &path.0,
+                            &path,
                             &(*dijkstra).get_query_export().visited_edges,
                             &data.nodes,
                             &data.graph.edges,
@@ -296,28 +439,28 @@ fn main() {
             let mut not_correct = 0;
             let mut no_path_found = 0;
             let mut not_no_path_found = 0;
-            for query in &eval {
-                let normal_result = debug_dijkstra.find_path(
-                    query.start_id.unwrap(),
-                    query.end_id.unwrap(),
-                    query.alpha.clone(),
-                    &data.graph,
-                    &data.nodes,
-                    &data.mlp_levels,
-                );
-                let result = dijkstra.find_path(
-                    query.start_id.unwrap(),
-                    query.end_id.unwrap(),
-                    query.alpha.clone(),
-                    &data.graph,
-                    &data.nodes,
-                    &data.mlp_levels,
-                );
+            for (query, alpha) in eval.iter().zip(&alphas) {
+                let normal_result = debug_dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
+                let result = dijkstra
+                    .find_path(&QueryRequest::on(
+                        &data,
+                        query.start_id.unwrap(),
+                        query.end_id.unwrap(),
+                        alpha,
+                    ))
+                    .unwrap();
                 match (normal_result, result) {
                     (Some(normal_result), Some(result)) => {
                         // only check costs of paths, because there can be multiple paths with same value
-                        if (cost_of_path(&query.alpha, &normal_result.0, &data.graph)
-                            - cost_of_path(&query.alpha, &result.0, &data.graph))
+                        if (cost_of_path(alpha, &normal_result.path, &data.graph)
+                            - cost_of_path(alpha, &result.path, &data.graph))
                         .abs()
                             < 1.0
                         {
@@ -329,10 +472,10 @@ fn main() {
                                 query.id,
                                 query.start_id.unwrap(),
                                 query.end_id.unwrap(),
-                                normal_result.1,
-                                cost_of_path(&query.alpha, &normal_result.0, &data.graph),
-                                result.1,
-                                cost_of_path(&query.alpha, &result.0, &data.graph),
+                                normal_result.cost,
+                                cost_of_path(alpha, &normal_result.path, &data.graph),
+                                result.cost,
+                                cost_of_path(alpha, &result.path, &data.graph),
                             );
                         }
                     }
@@ -371,6 +514,62 @@ fn main() {
     }
 }
 
+/// mean, median and 95th percentile of every phase in microseconds
+fn print_phase_table(export_list: &[PhaseExport]) {
+    if export_list.is_empty() {
+        return;
+    }
+    let total: u128 = export_list.iter().map(|query| query.total).sum();
+    println!(
+        "{:<10} {:>12} {:>12} {:>12} {:>8}",
+        "phase", "mean [µs]", "median [µs]", "p95 [µs]", "share"
+    );
+    for phase in &Phase::ALL {
+        let mut durations: Vec<u128> = export_list
+            .iter()
+            .map(|query| query.phases[phase])
+            .collect();
+        durations.sort_unstable();
+        let sum: u128 = durations.iter().sum();
+        let percentile = |p: usize| durations[(durations.len() - 1) * p / 100] as f64 / 1000.0;
+        println!(
+            "{:<10} {:>12.1} {:>12.1} {:>12.1} {:>7.1}%",
+            phase.to_string(),
+            sum as f64 / durations.len() as f64 / 1000.0,
+            percentile(50),
+            percentile(95),
+            sum as f64 / total.max(1) as f64 * 100.0
+        );
+    }
+}
+
+/// milliseconds for summing up the edge-ids of every node, once borrowed and once collected.
+/// the collected variant is how the adjacency was read before it returned iterators
+fn bench_adjacency(graph: &Graph) -> (f64, f64) {
+    let amount_nodes = graph.up_offset.len() - 1;
+
+    let iterate_time = Instant::now();
+    let mut sum = 0;
+    for node in 0..amount_nodes {
+        sum += graph.get_up_edge_ids(node).sum::<EdgeId>();
+        sum += graph.get_down_edge_ids(node).sum::<EdgeId>();
+    }
+    let iterate = iterate_time.elapsed().as_secs_f64() * 1000.0;
+
+    let collect_time = Instant::now();
+    let mut collected_sum = 0;
+    for node in 0..amount_nodes {
+        let up: Vec<EdgeId> = graph.get_up_edge_ids(node).collect();
+        let down: Vec<EdgeId> = graph.get_down_edge_ids(node).collect();
+        collected_sum += up.iter().sum::<EdgeId>() + down.iter().sum::<EdgeId>();
+    }
+    let collect = collect_time.elapsed().as_secs_f64() * 1000.0;
+
+    // keeps both loops from being optimized away
+    assert_eq!(sum, collected_sum);
+    (iterate, collect)
+}
+
 fn cost_of_path(alpha: &[Cost], path: &[EdgeId], graph: &Graph) -> f64 {
     let mut cost: f64 = 0.0;
     for edge in path {
@@ -386,6 +585,7 @@ fn get_arguments() -> (
     QueryType,
     bool,
     Option<String>,
+    usize,
 ) {
     let matches = clap::Command::new("prp_eval")
         .version(clap::crate_version!())
@@ -444,6 +644,15 @@ fn get_arguments() -> (
                 .short('x')
                 .long("export"),
         )
+        .arg(
+            clap::Arg::new("rounds")
+                .help("how often all queries are repeated when benchmarking")
+                .num_args(1)
+                .short('r')
+                .long("rounds")
+                .default_value("5")
+                .value_parser(clap::value_parser!(usize)),
+        )
         .get_matches();
 
     let eval_type = matches.get_one::<Vals>("type");
@@ -461,5 +670,6 @@ fn get_arguments() -> (
         *query_type,
         matches.get_flag("graph-info"),
         matches.get_one::<String>("export-path").cloned(),
+        *matches.get_one::<usize>("rounds").unwrap(),
     )
 }
@@ -0,0 +1,421 @@
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::str::FromStr;
+
+use prp_query::query_export::*;
+use prp_query::*;
+
+#[derive(Copy, Clone)]
+enum Format {
+    GeoJson,
+    Gpx,
+    Wkt,
+}
+
+impl FromStr for Format {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geojson" => Ok(Format::GeoJson),
+            "gpx" => Ok(Format::Gpx),
+            "wkt" => Ok(Format::Wkt),
+            _ => Err("no match"),
+        }
+    }
+}
+
+/// a point given as "latitude,longitude" or as node-id
+#[derive(Clone, Debug)]
+enum Position {
+    Coordinates(Location),
+    NodeId(NodeId),
+}
+
+impl FromStr for Position {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(',') {
+            Some((latitude, longitude)) => {
+                let parse = |value: &str| {
+                    value
+                        .trim()
+                        .parse::<Angle>()
+                        .map_err(|_| format!("invalid coordinate '{}'", value))
+                };
+                Ok(Position::Coordinates(Location {
+                    latitude: parse(latitude)?,
+                    longitude: parse(longitude)?,
+                }))
+            }
+            None => s
+                .trim()
+                .parse()
+                .map(Position::NodeId)
+                .map_err(|_| format!("'{}' is neither latitude,longitude nor a node-id", s)),
+        }
+    }
+}
+
+struct Route {
+    start_id: NodeId,
+    end_id: NodeId,
+    coordinates: Vec<(Angle, Angle)>,
+    cost: Cost,
+    metric_costs: Vec<Cost>,
+}
+
+fn main() {
+    let (fmi_file, query_type, format, start, end, alpha, normalize) = get_arguments();
+    let data: WebData = match bin_import::load_web_data(&fmi_file) {
+        Ok(result) => result,
+        Err(error) => panic!("error while reading bin-file: {:?}", error),
+    };
+    let mut dijkstra = dijkstra::get(query_type, data.nodes.len(), NoOp::new());
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if let Format::Wkt = format {
+        writeln!(out, "WKT;cost;{}", data.metrics.join(";")).unwrap();
+    }
+
+    // single query from the arguments
+    if let (Some(start), Some(end)) = (start, end) {
+        let route = route(
+            &data,
+            &mut dijkstra,
+            &start,
+            &end,
+            alpha.as_ref(),
+            normalize,
+        );
+        let failed = route.is_err();
+        write_route(&mut out, format, &data, route).unwrap();
+        if failed {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // one query per line: start end [alpha]
+    for (number, line) in io::stdin().lock().lines().enumerate() {
+        let line = line.expect("stdin should be readable");
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let route = parse_line(&line).and_then(|(start, end, line_alpha)| {
+            route(
+                &data,
+                &mut dijkstra,
+                &start,
+                &end,
+                line_alpha.as_ref().or(alpha.as_ref()),
+                normalize,
+            )
+        });
+        if let Err(error) = &route {
+            eprintln!("line {}: {}", number + 1, error);
+        }
+        write_route(&mut out, format, &data, route).unwrap();
+    }
+}
+
+/// split a line into start, end and an optional alpha
+fn parse_line(line: &str) -> Result<(Position, Position, Option<Alpha>), String> {
+    let mut parts = line.split_whitespace();
+    let mut next = |name: &str| parts.next().ok_or(format!("missing {}", name));
+    let start = next("start")?.parse()?;
+    let end = next("end")?.parse()?;
+    let alpha = parts.next().map(parse_alpha).transpose()?;
+    Ok((start, end, alpha))
+}
+
+/// alpha as json list or map, or as comma separated weights
+fn parse_alpha(value: &str) -> Result<Alpha, String> {
+    if value.starts_with('[') || value.starts_with('{') {
+        return serde_json::from_str(value).map_err(|error| format!("invalid alpha: {}", error));
+    }
+    value
+        .split(',')
+        .map(|weight| weight.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map(Alpha::Vector)
+        .map_err(|_| format!("invalid alpha '{}'", value))
+}
+
+fn snap(position: &Position, data: &WebData) -> Result<NodeId, String> {
+    match position {
+        Position::Coordinates(location) => match grid::get_closest_point(
+            location.clone(),
+            &data.nodes,
+            &data.grid,
+            &data.grid_offset,
+            &data.grid_bounds,
+        ) {
+            INVALID_NODE => Err(format!(
+                "no node near {},{}",
+                location.latitude, location.longitude
+            )),
+            node_id => Ok(node_id),
+        },
+        Position::NodeId(node_id) if *node_id < data.nodes.len() => Ok(*node_id),
+        Position::NodeId(node_id) => Err(format!("node-id {} is not in the graph", node_id)),
+    }
+}
+
+fn route(
+    data: &WebData,
+    dijkstra: &mut Box<dyn FindPath<NoOp>>,
+    start: &Position,
+    end: &Position,
+    alpha: Option<&Alpha>,
+    normalize: bool,
+) -> Result<Route, String> {
+    let alpha = alpha
+        .ok_or("alpha not found")?
+        .resolve_normalized(&data.metrics, normalize)
+        .map_err(|error| error.to_string())?;
+    let start_id = snap(start, data)?;
+    let end_id = snap(end, data)?;
+
+    let options = QueryOptions {
+        metric_costs: true,
+        ..QueryOptions::default()
+    };
+    let result = dijkstra
+        .find_path(&QueryRequest::on(data, start_id, end_id, &alpha).with_options(options))
+        .map_err(|reason| reason.to_string())?
+        .ok_or("no path found")?;
+
+    let coordinates =
+        grid::get_coordinates(data.graph.get_path_node_ids(&result.path), &data.nodes)
+            .iter()
+            .map(|node| (node.longitude, node.latitude))
+            .collect();
+    Ok(Route {
+        start_id,
+        end_id,
+        coordinates,
+        cost: result.cost,
+        metric_costs: result.metric_costs.unwrap(),
+    })
+}
+
+/// print a route, failed queries are printed as empty routes, so every query has an output
+fn write_route(
+    out: &mut impl Write,
+    format: Format,
+    data: &WebData,
+    route: Result<Route, String>,
+) -> io::Result<()> {
+    match format {
+        Format::GeoJson => {
+            let feature = match route {
+                Ok(route) => {
+                    let costs: BTreeMap<&String, Cost> = data
+                        .metrics
+                        .iter()
+                        .zip(route.metric_costs.iter().copied())
+                        .collect();
+                    json!({
+                        "type": "Feature",
+                        "geometry": {
+                            "type": "LineString",
+                            "coordinates": route.coordinates,
+                        },
+                        "properties": {
+                            "start_id": route.start_id,
+                            "end_id": route.end_id,
+                            "cost": route.cost,
+                            "costs": costs,
+                        },
+                    })
+                }
+                Err(error) => json!({
+                    "type": "Feature",
+                    "geometry": null,
+                    "properties": { "error": error },
+                }),
+            };
+            writeln!(out, "{}", feature)
+        }
+        Format::Gpx => {
+            writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+            writeln!(
+                out,
+                r#"<gpx version="1.1" creator="prp_route" xmlns="http://www.topografix.com/GPX/1/1">"#
+            )?;
+            writeln!(out, "  <trk>")?;
+            match route {
+                Ok(route) => {
+                    writeln!(
+                        out,
+                        "    <name>{} - {}</name>",
+                        route.start_id, route.end_id
+                    )?;
+                    let costs: Vec<String> = data
+                        .metrics
+                        .iter()
+                        .zip(&route.metric_costs)
+                        .map(|(metric, cost)| format!("{}={:.2}", xml_escape(metric), cost))
+                        .collect();
+                    writeln!(
+                        out,
+                        "    <desc>cost={:.2} {}</desc>",
+                        route.cost,
+                        costs.join(" ")
+                    )?;
+                    writeln!(out, "    <trkseg>")?;
+                    for (longitude, latitude) in &route.coordinates {
+                        writeln!(
+                            out,
+                            r#"      <trkpt lat="{}" lon="{}"/>"#,
+                            latitude, longitude
+                        )?;
+                    }
+                    writeln!(out, "    </trkseg>")?;
+                }
+                Err(error) => writeln!(out, "    <desc>{}</desc>", xml_escape(&error))?,
+            }
+            writeln!(out, "  </trk>")?;
+            writeln!(out, "</gpx>")
+        }
+        Format::Wkt => match route {
+            Ok(route) => {
+                let points: Vec<String> = route
+                    .coordinates
+                    .iter()
+                    .map(|(longitude, latitude)| format!("{} {}", longitude, latitude))
+                    .collect();
+                let costs: Vec<String> = route
+                    .metric_costs
+                    .iter()
+                    .map(|cost| cost.to_string())
+                    .collect();
+                writeln!(
+                    out,
+                    "LINESTRING ({});{};{}",
+                    points.join(", "),
+                    route.cost,
+                    costs.join(";")
+                )
+            }
+            Err(_) => writeln!(out, "LINESTRING EMPTY;{}", ";".repeat(data.metrics.len())),
+        },
+    }
+}
+
+#[allow(clippy::type_complexity)]
+/// metric names and error messages may contain characters with a meaning in xml
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn get_arguments() -> (
+    String,
+    QueryType,
+    Format,
+    Option<Position>,
+    Option<Position>,
+    Option<Alpha>,
+    bool,
+) {
+    let matches = clap::Command::new("prp_route")
+        .version(clap::crate_version!())
+        .author(clap::crate_authors!())
+        .about("routes single queries, from the arguments or line by line from stdin")
+        .arg(
+            clap::Arg::new("fmi-file")
+                .help("the input file to use")
+                .num_args(1)
+                .short('f')
+                .long("file")
+                .required(true),
+        )
+        .arg(
+            clap::Arg::new("query")
+                .help("What type of query will be used")
+                .num_args(1)
+                .short('q')
+                .long("query")
+                .default_value("prp")
+                .value_parser(clap::value_parser!(QueryType)),
+        )
+        .arg(
+            clap::Arg::new("start")
+                .help(
+                    "start as latitude,longitude or node-id, otherwise queries are read from stdin",
+                )
+                .num_args(1)
+                .short('s')
+                .long("start")
+                .requires("end")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            clap::Arg::new("end")
+                .help("end as latitude,longitude or node-id")
+                .num_args(1)
+                .short('e')
+                .long("end")
+                .requires("start")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            clap::Arg::new("alpha")
+                .help("weights as comma separated list, json list or json map of metric names")
+                .num_args(1)
+                .short('a')
+                .long("alpha"),
+        )
+        .arg(
+            clap::Arg::new("normalize")
+                .help("scale alpha to sum up to 1")
+                .long("normalize")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("output")
+                .help("output format: geojson, gpx or wkt")
+                .num_args(1)
+                .short('o')
+                .long("output")
+                .default_value("geojson")
+                .value_parser(clap::value_parser!(Format)),
+        )
+        .get_matches();
+
+    let position = |name: &str| {
+        matches
+            .get_one::<String>(name)
+            .map(|value| match value.parse::<Position>() {
+                Ok(position) => position,
+                Err(error) => panic!("invalid {}: {}", name, error),
+            })
+    };
+    let alpha = matches
+        .get_one::<String>("alpha")
+        .map(|value| match parse_alpha(value) {
+            Ok(alpha) => alpha,
+            Err(error) => panic!("{}", error),
+        });
+
+    (
+        matches
+            .get_one::<String>("fmi-file")
+            .expect("`fmi-file` is required")
+            .to_string(),
+        *matches.get_one::<QueryType>("query").unwrap(),
+        *matches.get_one::<Format>("output").unwrap(),
+        position("start"),
+        position("end"),
+        alpha,
+        matches.get_flag("normalize"),
+    )
+}
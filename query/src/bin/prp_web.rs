@@ -1,107 +1,128 @@
 #[macro_use]
 extern crate log;
 
-use actix_web::{get, middleware, post, web, App, HttpServer};
+use actix_web::dev::{ServerHandle, Service};
+use actix_web::{delete, get, middleware, post, put, rt, web, App, HttpResponse, HttpServer};
 use rayon::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::Path;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use prp_query::elicitation::*;
 use prp_query::geojson::*;
+use prp_query::graph_registry::*;
+use prp_query::monitoring::*;
+use prp_query::profiles::*;
 use prp_query::query_export::*;
+use prp_query::tradeoff::{self, Tradeoff};
 use prp_query::*;
 
+// nodes tried for each clicked point if the closest ones are not connected
+const SNAP_CANDIDATES: usize = 3;
+const SNAP_ATTEMPTS: usize = 5;
+// relative difference of costs, that still counts as the same optimum when comparing algorithms
+const COST_TOLERANCE: Cost = 1e-6;
+
+// thread-local dijkstra per graph and query type together with the generation of the graph it was built for
+type EngineMap<E> = HashMap<(String, QueryType), (usize, Box<dyn FindPath<E>>)>;
+type Engines = RefCell<EngineMap<Counter>>;
+// separate dijkstras recording the whole search space for debugging
+type DebugEngines = RefCell<EngineMap<RealExport>>;
+
 #[post("/dijkstra")]
 async fn shortest_path(
     request: web::Json<GeoJsonRequest>,
-    data: web::Data<WebData>,
-    dijkstra_cell: web::Data<RefCell<Box<dyn FindPath<NoOp>>>>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    monitoring: web::Data<Monitoring>,
+    profiles: web::Data<ProfileStore>,
 ) -> Result<web::Json<GeoJsonResponse>, geojson::Error> {
-    let total_time = Instant::now();
-
-    // extract points
-    let features = &request.features;
-    assert_eq!(features.len(), 2);
+    let graph = get_graph(&registry, None)?;
+    find_shortest_path(&request, &graph, &engines, &monitoring, &profiles).map(web::Json)
+}
 
-    let start_feature = &features[0].geometry.coordinates;
-    let end_feature = &features[1].geometry.coordinates;
-    assert_eq!(start_feature.len(), 2);
-    assert_eq!(end_feature.len(), 2);
+#[post("/graphs/{graph}/dijkstra")]
+async fn graph_shortest_path(
+    name: web::Path<String>,
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    monitoring: web::Data<Monitoring>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<GeoJsonResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    find_shortest_path(&request, &graph, &engines, &monitoring, &profiles).map(web::Json)
+}
 
-    let start = Location {
-        longitude: start_feature[0],
-        latitude: start_feature[1],
-    };
-    let end = Location {
-        longitude: end_feature[0],
-        latitude: end_feature[1],
-    };
-    // find alpha as property at any node from last node to front
-    let mut alpha_option = None;
-    for feature in features.iter().rev() {
-        alpha_option = match &feature.properties {
-            Some(properties) => properties.alpha.clone(),
-            None => alpha_option,
-        };
-    }
-    // return Error if no alpha is set
-    if alpha_option.is_none() {
-        return Err(geojson::Error {
-            msg: "alpha not found".to_string(),
-            status: 400,
-        });
-    }
-    let alpha = alpha_option.unwrap();
-    if alpha.len() != data.graph.dim {
-        return Err(geojson::Error {
-            msg: "alpha vector-size does not match".to_string(),
-            status: 400,
-        });
-    }
+fn find_shortest_path(
+    request: &GeoJsonRequest,
+    graph: &LoadedGraph,
+    engines: &Engines,
+    monitoring: &Monitoring,
+    profiles: &ProfileStore,
+) -> Result<GeoJsonResponse, geojson::Error> {
+    let total_time = Instant::now();
+    let data = &graph.data;
 
-    debug!("Start: {},{}", start.latitude, start.longitude);
-    debug!("End: {},{}", end.latitude, end.longitude);
-    debug!("Alpha: {:?}", alpha);
+    let (start, end, alpha) = parse_request(request, graph, profiles)?;
+    let query_type = requested_query_type(request, graph);
 
     // search for clicked points
     let grid_time = Instant::now();
-    let start_id: NodeId = grid::get_closest_point(
-        start,
-        &data.nodes,
-        &data.grid,
-        &data.grid_offset,
-        &data.grid_bounds,
-    );
-    let end_id: NodeId = grid::get_closest_point(
-        end,
-        &data.nodes,
-        &data.grid,
-        &data.grid_offset,
-        &data.grid_bounds,
-    );
-    debug!("start_id {}", start_id);
-    debug!("end_id {}", end_id);
-    info!(" Get node-ID in: {:?}", grid_time.elapsed());
+    let start_candidates = snap_candidates(start, SNAP_CANDIDATES, graph)?;
+    let end_candidates = snap_candidates(end, SNAP_CANDIDATES, graph)?;
+    debug!("start candidates {:?}", start_candidates);
+    debug!("end candidates {:?}", end_candidates);
+    let snapping_time = grid_time.elapsed();
+    info!(" Get node-ID in: {:?}", snapping_time);
 
-    let mut dijkstra = dijkstra_cell.borrow_mut();
+    let mut engines = engines.borrow_mut();
+    let dijkstra = get_engine(&mut engines, graph, query_type);
 
+    let dominant_metric = dominant_metric(&alpha, &data.metrics);
     let dijkstra_time = Instant::now();
-    let tmp = dijkstra.find_path(
-        start_id,
-        end_id,
-        alpha,
-        &data.graph,
-        &data.nodes,
-        &data.mlp_levels,
-    );
-    info!("    Dijkstra in: {:?}", dijkstra_time.elapsed());
+    // all retries share the deadline of the request
+    let options = QueryOptions {
+        limits: graph.query_limits(),
+        ..QueryOptions::default()
+    };
+    // try the closest pairs first, the next candidates if they are not connected
+    let mut pairs: Vec<(usize, usize)> = (0..start_candidates.len())
+        .flat_map(|i| (0..end_candidates.len()).map(move |j| (i, j)))
+        .collect();
+    pairs.sort_by_key(|(i, j)| i + j);
+    let (mut start_index, mut end_index) = pairs[0];
+    let mut tmp = None;
+    for (i, j) in pairs.into_iter().take(SNAP_ATTEMPTS) {
+        let request = QueryRequest::on(data, start_candidates[i].0, end_candidates[j].0, &alpha)
+            .with_options(options.clone());
+        start_index = i;
+        end_index = j;
+        tmp = dijkstra.find_path(&request).map_err(abort_error)?;
+        if tmp.is_some() {
+            break;
+        }
+    }
+    let (start_id, start_distance) = start_candidates[start_index];
+    let (end_id, end_distance) = end_candidates[end_index];
+    debug!("start_id {}", start_id);
+    debug!("end_id {}", end_id);
+    let search_time = dijkstra_time.elapsed();
+    info!("    Dijkstra in: {:?}", search_time);
+    let found_path = tmp.is_some();
+    let chosen_path = tmp.as_ref().map(|result| result.path.clone());
 
     let (result_path, cost): (Vec<(Angle, Angle)>, String) = match tmp {
-        Some((path, path_cost)) => {
-            let nodes = grid::get_coordinates(
-                convert_edge_ids_to_node_ids(&path, &data.graph),
-                &data.nodes,
-            );
+        Some(QueryResult {
+            path,
+            cost: path_cost,
+            ..
+        }) => {
+            let nodes = grid::get_coordinates(data.graph.get_path_node_ids(&path), &data.nodes);
             (
                 nodes
                     .par_iter()
@@ -116,9 +137,42 @@ async fn shortest_path(
         }
     };
 
-    info!("        Overall: {:?}", total_time.elapsed());
+    let overall_time = total_time.elapsed();
+    info!("        Overall: {:?}", overall_time);
+
+    monitoring.record_query(
+        &graph.config.name,
+        query_type,
+        &dominant_metric,
+        &QueryTimings {
+            snapping: snapping_time,
+            search: search_time,
+            total: overall_time,
+        },
+        dijkstra.get_query_export(),
+        found_path,
+    );
+
+    let compare = request.features.iter().any(|feature| {
+        feature
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.compare)
+            .unwrap_or(false)
+    });
+    let tradeoffs = match chosen_path {
+        Some(path) if compare => Some(find_tradeoffs(
+            &path,
+            start_id,
+            end_id,
+            graph,
+            dijkstra,
+            &options.limits,
+        )?),
+        _ => None,
+    };
 
-    Ok(web::Json(GeoJsonResponse {
+    Ok(GeoJsonResponse {
         // escaping the rust-type command to normal type string
         r#type: "FeatureCollection".to_string(),
         features: vec![FeatureResponse {
@@ -130,64 +184,936 @@ async fn shortest_path(
             properties: Some(Property {
                 cost: Some(cost),
                 alpha: None,
+                normalize: None,
+                profile_id: None,
+                query_type: None,
+                compare: None,
+                tradeoffs,
+                snap_distance: Some(vec![start_distance, end_distance]),
             }),
         }],
-    }))
+    })
+}
+
+/// compare the chosen path against the optimal path of every single metric
+fn find_tradeoffs(
+    chosen_path: &[EdgeId],
+    start_id: NodeId,
+    end_id: NodeId,
+    graph: &LoadedGraph,
+    dijkstra: &mut Box<dyn FindPath<Counter>>,
+    limits: &QueryLimits,
+) -> Result<Vec<Tradeoff>, geojson::Error> {
+    let data = &graph.data;
+    let chosen_costs = data.graph.get_path_costs(chosen_path);
+    let mut tradeoffs = Vec::with_capacity(data.metrics.len());
+    // all alternatives share the deadline of the request
+    let options = QueryOptions {
+        metric_costs: true,
+        limits: limits.clone(),
+        ..QueryOptions::default()
+    };
+    for (metric_id, metric) in data.metrics.iter().enumerate() {
+        let alpha = tradeoff::unit_alpha(metric_id, data.graph.dim);
+        let request =
+            QueryRequest::on(data, start_id, end_id, &alpha).with_options(options.clone());
+        if let Some(alternative) = dijkstra.find_path(&request).map_err(abort_error)? {
+            tradeoffs.push(tradeoff::compare(
+                metric,
+                &chosen_costs,
+                &alternative.metric_costs.unwrap(),
+                alternative.path == chosen_path,
+                &data.metrics,
+            ));
+        }
+    }
+    Ok(tradeoffs)
+}
+
+#[post("/debug/algorithms")]
+async fn all_algorithms(
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<AlgorithmsResponse>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    compare_algorithms(&request, &graph, &engines, &profiles).map(web::Json)
+}
+
+#[post("/graphs/{graph}/debug/algorithms")]
+async fn graph_all_algorithms(
+    name: web::Path<String>,
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<AlgorithmsResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    compare_algorithms(&request, &graph, &engines, &profiles).map(web::Json)
+}
+
+/// run every algorithm on the same request and check them against the normal dijkstra
+fn compare_algorithms(
+    request: &GeoJsonRequest,
+    graph: &LoadedGraph,
+    engines: &Engines,
+    profiles: &ProfileStore,
+) -> Result<AlgorithmsResponse, geojson::Error> {
+    let data = &graph.data;
+    let (start, end, alpha) = parse_request(request, graph, profiles)?;
+    let start_id = snap_to_graph(start, graph)?;
+    let end_id = snap_to_graph(end, graph)?;
+
+    let mut engines = engines.borrow_mut();
+    let mut results: Vec<AlgorithmResult> = Vec::with_capacity(QueryType::ALL.len());
+    for query_type in &QueryType::ALL {
+        let dijkstra = get_engine(&mut engines, graph, *query_type);
+        // every algorithm gets its own time budget
+        let options = QueryOptions {
+            limits: graph.query_limits(),
+            ..QueryOptions::default()
+        };
+        let time = Instant::now();
+        let result = dijkstra
+            .find_path(&QueryRequest::on(data, start_id, end_id, &alpha).with_options(options));
+        let time = time.elapsed();
+        let counter = dijkstra.get_query_export();
+        results.push(AlgorithmResult {
+            query_type: *query_type,
+            cost: result
+                .as_ref()
+                .ok()
+                .and_then(|result| result.as_ref().map(|result| result.cost)),
+            time: time.as_micros(),
+            heap_pops: counter.heap_pops,
+            relaxed_edges: counter.relaxed_edges,
+            disagrees: false,
+            error: result.err().map(|reason| reason.to_string()),
+        });
+    }
+
+    // the normal dijkstra is the reference, aborted queries are not compared
+    let reference = results[0].cost;
+    if results[0].error.is_none() {
+        for result in results.iter_mut().filter(|result| result.error.is_none()) {
+            result.disagrees = match (reference, result.cost) {
+                (Some(reference), Some(cost)) => {
+                    (reference - cost).abs() > COST_TOLERANCE * reference.abs().max(1.0)
+                }
+                (None, None) => false,
+                _ => true,
+            };
+        }
+    }
+
+    Ok(AlgorithmsResponse {
+        start_id,
+        end_id,
+        costs_agree: !results.iter().any(|result| result.disagrees),
+        results,
+    })
+}
+
+#[post("/debug/search-space")]
+async fn search_space(
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<DebugEngines>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<SearchSpaceResponse>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    find_search_space(&request, &graph, &engines, &profiles).map(web::Json)
+}
+
+#[post("/graphs/{graph}/debug/search-space")]
+async fn graph_search_space(
+    name: web::Path<String>,
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<DebugEngines>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<SearchSpaceResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    find_search_space(&request, &graph, &engines, &profiles).map(web::Json)
+}
+
+/// run the query recording every settled edge, split into forward and backward search
+fn find_search_space(
+    request: &GeoJsonRequest,
+    graph: &LoadedGraph,
+    engines: &DebugEngines,
+    profiles: &ProfileStore,
+) -> Result<SearchSpaceResponse, geojson::Error> {
+    let data = &graph.data;
+    let (start, end, alpha) = parse_request(request, graph, profiles)?;
+    let start_id = snap_to_graph(start, graph)?;
+    let end_id = snap_to_graph(end, graph)?;
+
+    let mut engines = engines.borrow_mut();
+    let dijkstra = get_engine(&mut engines, graph, requested_query_type(request, graph));
+    let options = QueryOptions {
+        limits: graph.query_limits(),
+        ..QueryOptions::default()
+    };
+    let result = dijkstra
+        .find_path(&QueryRequest::on(data, start_id, end_id, &alpha).with_options(options))
+        .map_err(abort_error)?;
+    let export = dijkstra.get_query_export();
+
+    let amount_levels = data.mlp_levels.len();
+    let mut forward = Vec::new();
+    let mut backward = Vec::new();
+    for (edge_id, settled) in export
+        .visited_edges
+        .iter()
+        .zip(export.visited_edges_settled.iter())
+    {
+        let edge = &data.graph.edges[*edge_id];
+        // the forward search settles the head of an edge, the backward search its tail
+        let (layer, hue) = if edge.to == *settled {
+            (&mut forward, 210)
+        } else {
+            (&mut backward, 0)
+        };
+        layer.push(SearchSpaceFeature {
+            r#type: "Feature".to_string(),
+            properties: SearchSpaceProperty {
+                level: edge.level,
+                color: level_color(hue, edge.level, amount_levels),
+            },
+            geometry: GeometryResponse {
+                r#type: "LineString".to_string(),
+                coordinates: vec![
+                    (
+                        data.nodes[edge.from].longitude,
+                        data.nodes[edge.from].latitude,
+                    ),
+                    (data.nodes[edge.to].longitude, data.nodes[edge.to].latitude),
+                ],
+            },
+        });
+    }
+    let layer = |features| SearchSpaceLayer {
+        r#type: "FeatureCollection".to_string(),
+        features,
+    };
+
+    Ok(SearchSpaceResponse {
+        forward: layer(forward),
+        backward: layer(backward),
+        meeting_node: export
+            .meeting_node
+            .map(|node| (data.nodes[node].longitude, data.nodes[node].latitude)),
+        cost: result.map(|result| format!("{:.2}", result.cost)),
+        heap_pops: export.heap_pops,
+        relaxed_edges: export.relaxed_edges,
+        visited_nodes: export.visited_nodes.len(),
+    })
+}
+
+/// css color of a search-space edge, higher mlp-levels are drawn darker
+fn level_color(hue: usize, level: Option<Level>, amount_levels: usize) -> String {
+    let lightness = match level {
+        Some(level) if amount_levels > 0 => 70 - 45 * level.min(amount_levels) / amount_levels,
+        _ => 75,
+    };
+    format!("hsl({}, 80%, {}%)", hue, lightness)
+}
+
+/// extract start, end and alpha of a request.
+/// alpha is taken from the request, a stored profile or the default of the graph
+fn parse_request(
+    request: &GeoJsonRequest,
+    graph: &LoadedGraph,
+    profiles: &ProfileStore,
+) -> Result<(Location, Location, Vec<f64>), geojson::Error> {
+    let data = &graph.data;
+    let (start, end) = parse_locations(request)?;
+    let features = &request.features;
+
+    // find alpha as property at any node from last node to front
+    let mut alpha_option = None;
+    for feature in features.iter().rev() {
+        alpha_option = match &feature.properties {
+            Some(properties) => properties.alpha.clone(),
+            None => alpha_option,
+        };
+    }
+    let normalize = features.iter().any(|feature| {
+        feature
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.normalize)
+            .unwrap_or(false)
+    });
+    // use a stored profile instead
+    if alpha_option.is_none() {
+        let profile_id = features
+            .iter()
+            .find_map(|feature| feature.properties.as_ref()?.profile_id);
+        if let Some(profile_id) = profile_id {
+            alpha_option = Some(Alpha::Vector(
+                profiles
+                    .alpha_for(profile_id, &graph.config.name, &data.metrics)
+                    .map_err(profile_error)?,
+            ));
+        }
+    }
+    // fall back to the default alpha of the graph
+    if alpha_option.is_none() {
+        alpha_option = graph.config.default_alpha.clone();
+    }
+    // return Error if no alpha is set
+    if alpha_option.is_none() {
+        return Err(geojson::Error {
+            msg: "alpha not found".to_string(),
+            status: 400,
+        });
+    }
+    let alpha = alpha_option
+        .unwrap()
+        .resolve_normalized(&data.metrics, normalize)
+        .map_err(|error| geojson::Error {
+            msg: error.to_string(),
+            status: 400,
+        })?;
+
+    debug!("Graph: {}", graph.config.name);
+    debug!("Start: {},{}", start.latitude, start.longitude);
+    debug!("End: {},{}", end.latitude, end.longitude);
+    debug!("Alpha: {:?}", alpha);
+
+    Ok((start, end, alpha))
+}
+
+/// algorithm requested in any feature, the query type of the graph otherwise
+fn requested_query_type(request: &GeoJsonRequest, graph: &LoadedGraph) -> QueryType {
+    request
+        .features
+        .iter()
+        .find_map(|feature| feature.properties.as_ref()?.query_type)
+        .unwrap_or(graph.config.query_type)
+}
+
+/// extract start and end point of a request
+fn parse_locations(request: &GeoJsonRequest) -> Result<(Location, Location), geojson::Error> {
+    let features = &request.features;
+    if features.len() != 2
+        || features[0].geometry.coordinates.len() != 2
+        || features[1].geometry.coordinates.len() != 2
+    {
+        return Err(geojson::Error {
+            msg: "expected start and end point".to_string(),
+            status: 400,
+        });
+    }
+
+    let start_feature = &features[0].geometry.coordinates;
+    let end_feature = &features[1].geometry.coordinates;
+
+    let start = Location {
+        longitude: start_feature[0],
+        latitude: start_feature[1],
+    };
+    let end = Location {
+        longitude: end_feature[0],
+        latitude: end_feature[1],
+    };
+    Ok((start, end))
+}
+
+/// closest node, optionally preferring the main component over islands
+fn snap_to_graph(location: Location, graph: &LoadedGraph) -> Result<NodeId, geojson::Error> {
+    Ok(snap_candidates(location, 1, graph)?[0].0)
+}
+
+/// the k closest nodes within the maximum snap distance and their distances in meters.
+/// nodes of the main component are preferred if configured
+fn snap_candidates(
+    location: Location,
+    k: usize,
+    graph: &LoadedGraph,
+) -> Result<Vec<(NodeId, Angle)>, geojson::Error> {
+    let data = &graph.data;
+    let max_distance = graph.config.max_snap_distance;
+    let closest = |accept: &dyn Fn(NodeId) -> bool| {
+        grid::get_closest_points(
+            location.clone(),
+            k,
+            max_distance,
+            &data.nodes,
+            &data.grid,
+            &data.grid_offset,
+            &data.grid_bounds,
+            accept,
+        )
+    };
+    let mut candidates = Vec::new();
+    if graph.config.snap_to_main_component {
+        candidates = closest(&|node_id| data.components.is_in_main(node_id));
+    }
+    if candidates.is_empty() {
+        candidates = closest(&|_| true);
+    }
+    if candidates.is_empty() {
+        return Err(geojson::Error {
+            msg: format!(
+                "no node within {} m of ({}, {})",
+                max_distance.unwrap_or(f64::MAX),
+                location.latitude,
+                location.longitude
+            ),
+            status: 404,
+        });
+    }
+    Ok(candidates)
 }
 
 #[get("/metrics")]
 async fn metrics(
-    data: web::Data<WebData>,
-    _dijkstra_cell: web::Data<RefCell<Box<dyn FindPath<NoOp>>>>,
-) -> web::Json<Vec<String>> {
-    web::Json(data.metrics.clone())
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<Vec<String>>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    Ok(web::Json(graph.data.metrics.clone()))
+}
+
+#[get("/graphs/{graph}/metrics")]
+async fn graph_metrics(
+    name: web::Path<String>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<Vec<String>>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    Ok(web::Json(graph.data.metrics.clone()))
+}
+
+#[get("/info")]
+async fn info(
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<InfoResponse>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    Ok(web::Json(graph.info()))
+}
+
+#[get("/graphs/{graph}/info")]
+async fn graph_info(
+    name: web::Path<String>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<InfoResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    Ok(web::Json(graph.info()))
+}
+
+#[get("/nearest")]
+async fn nearest(
+    request: web::Query<Location>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<NearestResponse>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    find_nearest(request.into_inner(), &graph.data).map(web::Json)
+}
+
+#[get("/graphs/{graph}/nearest")]
+async fn graph_nearest(
+    name: web::Path<String>,
+    request: web::Query<Location>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<web::Json<NearestResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    find_nearest(request.into_inner(), &graph.data).map(web::Json)
 }
 
-fn convert_edge_ids_to_node_ids(edges: &[EdgeId], graph: &Graph) -> Vec<NodeId> {
-    if edges.is_empty() {
-        return vec![];
+fn find_nearest(location: Location, data: &WebData) -> Result<NearestResponse, geojson::Error> {
+    let (id, distance) = grid::get_closest_point_and_distance(
+        location,
+        &data.nodes,
+        &data.grid,
+        &data.grid_offset,
+        &data.grid_bounds,
+    );
+    if id == INVALID_NODE {
+        return Err(geojson::Error {
+            msg: "no node found".to_string(),
+            status: 404,
+        });
     }
-    let mut path: Vec<NodeId> = edges
-        .iter()
-        .map(|edge_id| graph.edges[*edge_id].from)
-        .collect();
-    path.push(graph.edges[*edges.last().unwrap()].to);
-    path
+    Ok(NearestResponse {
+        id,
+        latitude: data.nodes[id].latitude,
+        longitude: data.nodes[id].longitude,
+        distance_m: distance,
+    })
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    let (fmi_file, port, query_type) = get_arguments();
-    // read binfile
-    let mut data: BinFile = match bin_import::read_file(&fmi_file) {
-        Ok(result) => result,
-        Err(error) => panic!("error while reading bin-file: {:?}", error),
+#[get("/metrics/prometheus")]
+async fn prometheus(monitoring: web::Data<Monitoring>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(monitoring.render())
+}
+
+#[get("/tiles/{z}/{x}/{y}.mvt")]
+async fn tile(
+    path: web::Path<(u32, u32, u32)>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<HttpResponse, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    get_tile(path.into_inner(), &graph.data)
+}
+
+#[get("/graphs/{graph}/tiles/{z}/{x}/{y}.mvt")]
+async fn graph_tile(
+    path: web::Path<(String, u32, u32, u32)>,
+    registry: web::Data<GraphRegistry>,
+) -> Result<HttpResponse, geojson::Error> {
+    let (name, z, x, y) = path.into_inner();
+    let graph = get_graph(&registry, Some(&name))?;
+    get_tile((z, x, y), &graph.data)
+}
+
+fn get_tile((z, x, y): (u32, u32, u32), data: &WebData) -> Result<HttpResponse, geojson::Error> {
+    let tile_id = vector_tile::TileId::new(z, x, y).ok_or_else(|| geojson::Error {
+        msg: format!("invalid tile {}/{}/{}", z, x, y),
+        status: 400,
+    })?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/vnd.mapbox-vector-tile")
+        .body(vector_tile::graph_tile(&tile_id, data)))
+}
+
+// route pairs tried per round, before giving up on distinguishable routes
+const CANDIDATE_PAIRS: usize = 8;
+
+#[post("/elicitation")]
+async fn start_elicitation(
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    sessions: web::Data<ElicitationStore>,
+) -> Result<web::Json<ElicitationResponse>, geojson::Error> {
+    let graph = get_graph(&registry, None)?;
+    create_elicitation(&request, &graph, &engines, &sessions).map(web::Json)
+}
+
+#[post("/graphs/{graph}/elicitation")]
+async fn graph_start_elicitation(
+    name: web::Path<String>,
+    request: web::Json<GeoJsonRequest>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    sessions: web::Data<ElicitationStore>,
+) -> Result<web::Json<ElicitationResponse>, geojson::Error> {
+    let graph = get_graph(&registry, Some(&name))?;
+    create_elicitation(&request, &graph, &engines, &sessions).map(web::Json)
+}
+
+#[post("/elicitation/{id}")]
+async fn answer_elicitation(
+    id: web::Path<u64>,
+    request: web::Json<ElicitationChoice>,
+    registry: web::Data<GraphRegistry>,
+    engines: web::Data<Engines>,
+    sessions: web::Data<ElicitationStore>,
+) -> Result<web::Json<ElicitationResponse>, geojson::Error> {
+    let id = id.into_inner();
+    let mut session = sessions.get(id).ok_or_else(|| session_not_found(id))?;
+    let round = session.rounds;
+    session
+        .choose(request.choice)
+        .map_err(|error| geojson::Error {
+            msg: error,
+            status: 400,
+        })?;
+    let graph = get_graph(&registry, Some(&session.graph))?;
+    propose_routes(id, round, session, &graph, &engines, &sessions).map(web::Json)
+}
+
+fn session_not_found(id: u64) -> geojson::Error {
+    geojson::Error {
+        msg: format!("elicitation {} not found", id),
+        status: 404,
+    }
+}
+
+fn create_elicitation(
+    request: &GeoJsonRequest,
+    graph: &LoadedGraph,
+    engines: &Engines,
+    sessions: &ElicitationStore,
+) -> Result<ElicitationResponse, geojson::Error> {
+    let (start, end) = parse_locations(request)?;
+    // same points give the same proposals
+    let seed = start.latitude.to_bits()
+        ^ start.longitude.to_bits().rotate_left(16)
+        ^ end.latitude.to_bits().rotate_left(32)
+        ^ end.longitude.to_bits().rotate_left(48);
+    let session =
+        ElicitationSession::new(&graph.config.name, start, end, graph.data.graph.dim, seed);
+    let id = sessions.insert(session.clone());
+    propose_routes(id, 0, session, graph, engines, sessions)
+}
+
+/// compute the next pair of differing routes, or finish the session.
+/// `round` is the round of the stored session the proposal is based on
+fn propose_routes(
+    id: u64,
+    round: usize,
+    mut session: ElicitationSession,
+    graph: &LoadedGraph,
+    engines: &Engines,
+    sessions: &ElicitationStore,
+) -> Result<ElicitationResponse, geojson::Error> {
+    let data = &graph.data;
+    if session.region.dim() != data.graph.dim {
+        return Err(geojson::Error {
+            msg: "metrics of the graph changed during elicitation".to_string(),
+            status: 409,
+        });
+    }
+
+    if !session.is_converged() {
+        let start_id = snap_to_graph(session.start.clone(), graph)?;
+        let end_id = snap_to_graph(session.end.clone(), graph)?;
+        let mut engines = engines.borrow_mut();
+        let dijkstra = get_engine(&mut engines, graph, graph.config.query_type);
+        let options = QueryOptions {
+            metric_costs: true,
+            limits: graph.query_limits(),
+            ..QueryOptions::default()
+        };
+        let mut route = |alpha: Vec<f64>| {
+            let request =
+                QueryRequest::on(data, start_id, end_id, &alpha).with_options(options.clone());
+            dijkstra.find_path(&request).map(|result| {
+                result.map(|result| RouteOption {
+                    costs: result.metric_costs.unwrap(),
+                    path: result.path,
+                    alpha,
+                })
+            })
+        };
+        for (alpha_a, alpha_b) in session.region.candidate_pairs(CANDIDATE_PAIRS) {
+            let routes = (
+                route(alpha_a).map_err(abort_error)?,
+                route(alpha_b).map_err(abort_error)?,
+            );
+            match routes {
+                (Some(a), Some(b)) => {
+                    if a.path != b.path {
+                        session.options = vec![a, b];
+                        break;
+                    }
+                }
+                _ => {
+                    return Err(geojson::Error {
+                        msg: "no path found".to_string(),
+                        status: 404,
+                    })
+                }
+            }
+        }
+    }
+    // no differing routes left, so the answers can not narrow alpha any further
+    session.done = session.options.is_empty();
+
+    let alpha = session.region.estimate();
+    let response = ElicitationResponse {
+        session_id: id,
+        round: session.rounds,
+        done: session.done,
+        options: session
+            .options
+            .iter()
+            .map(|option| ElicitationOption {
+                alpha: option.alpha.clone(),
+                costs: data
+                    .metrics
+                    .iter()
+                    .cloned()
+                    .zip(option.costs.iter().copied())
+                    .collect(),
+                coordinates: data
+                    .graph
+                    .get_path_node_ids(&option.path)
+                    .iter()
+                    .map(|node| (data.nodes[*node].longitude, data.nodes[*node].latitude))
+                    .collect(),
+            })
+            .collect(),
+        profile: data
+            .metrics
+            .iter()
+            .cloned()
+            .zip(alpha.iter().copied())
+            .collect(),
+        alpha,
+    };
+    // the queries run without the lock, so only store if no other answer came first
+    sessions
+        .with_session_mut(id, |stored| {
+            if stored.rounds != round {
+                return Err(geojson::Error {
+                    msg: format!("elicitation {} was answered concurrently", id),
+                    status: 409,
+                });
+            }
+            *stored = session;
+            Ok(response)
+        })
+        .unwrap_or_else(|| Err(session_not_found(id)))
+}
+
+#[derive(Deserialize)]
+struct ProfileQuery {
+    user: Option<String>,
+}
+
+#[get("/profiles")]
+async fn list_profiles(
+    query: web::Query<ProfileQuery>,
+    profiles: web::Data<ProfileStore>,
+) -> web::Json<Vec<ProfileResponse>> {
+    web::Json(profiles.list(query.user.as_deref()))
+}
+
+#[post("/profiles")]
+async fn create_profile(
+    request: web::Json<ProfileRequest>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<ProfileResponse>, geojson::Error> {
+    profiles
+        .create(request.into_inner())
+        .map(web::Json)
+        .map_err(profile_error)
+}
+
+#[get("/profiles/{id}")]
+async fn get_profile(
+    id: web::Path<ProfileId>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<ProfileResponse>, geojson::Error> {
+    profiles
+        .get_response(id.into_inner())
+        .map(web::Json)
+        .map_err(profile_error)
+}
+
+#[put("/profiles/{id}")]
+async fn update_profile(
+    id: web::Path<ProfileId>,
+    request: web::Json<ProfileRequest>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<ProfileResponse>, geojson::Error> {
+    profiles
+        .update(id.into_inner(), request.into_inner())
+        .map(web::Json)
+        .map_err(profile_error)
+}
+
+#[delete("/profiles/{id}")]
+async fn delete_profile(
+    id: web::Path<ProfileId>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<HttpResponse, geojson::Error> {
+    profiles.delete(id.into_inner()).map_err(profile_error)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// limits of a query are exhausted, mostly because of a pathological alpha
+fn abort_error(reason: AbortReason) -> geojson::Error {
+    warn!("{}", reason);
+    geojson::Error {
+        msg: reason.to_string(),
+        status: match reason {
+            AbortReason::Deadline => 504,
+            _ => 503,
+        },
+    }
+}
+
+fn profile_error(error: ProfileError) -> geojson::Error {
+    let status = match error {
+        ProfileError::NotFound(_) => 404,
+        ProfileError::Invalid(_) => 400,
+        ProfileError::Storage(_) => 500,
+    };
+    geojson::Error {
+        msg: error.to_string(),
+        status,
+    }
+}
+
+/// validate stored profiles against the metrics of all loaded graphs
+fn validate_profiles(registry: &GraphRegistry, profiles: &ProfileStore) {
+    for name in registry.names() {
+        if let Some(graph) = registry.get(&name) {
+            for (id, stale) in profiles.graph_loaded(&name, &graph.data.metrics) {
+                warn!(
+                    "profile {} is stale for graph '{}' (missing: {:?}, unknown: {:?})",
+                    id, name, stale.missing, stale.unknown
+                );
+            }
+        }
+    }
+}
+
+#[get("/graphs")]
+async fn graphs(registry: web::Data<GraphRegistry>) -> web::Json<Vec<GraphStatus>> {
+    web::Json(registry.status())
+}
+
+#[get("/health")]
+async fn health() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": "ok" }))
+}
+
+#[get("/ready")]
+async fn ready(registry: web::Data<GraphRegistry>) -> HttpResponse {
+    let body = json!({
+        "ready": registry.is_ready(),
+        "graphs": registry.status(),
+    });
+    if registry.is_ready() {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+#[post("/admin/reload")]
+async fn reload_all(
+    registry: web::Data<GraphRegistry>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<Vec<GraphStatus>>, geojson::Error> {
+    let loading_registry = registry.clone();
+    let results = web::block(move || loading_registry.load_all())
+        .await
+        .map_err(|error| geojson::Error {
+            msg: error.to_string(),
+            status: 500,
+        })?;
+    validate_profiles(&registry, &profiles);
+    for (name, result) in results {
+        if let Err(error) = result {
+            return Err(geojson::Error {
+                msg: format!("reloading '{}' failed: {}", name, error),
+                status: 500,
+            });
+        }
+    }
+    Ok(web::Json(registry.status()))
+}
+
+#[post("/admin/reload/{graph}")]
+async fn reload_graph(
+    name: web::Path<String>,
+    registry: web::Data<GraphRegistry>,
+    profiles: web::Data<ProfileStore>,
+) -> Result<web::Json<Vec<GraphStatus>>, geojson::Error> {
+    let name = name.into_inner();
+    if !registry.contains(&name) {
+        return Err(geojson::Error {
+            msg: format!("unknown graph '{}'", name),
+            status: 404,
+        });
+    }
+    let loading_registry = registry.clone();
+    web::block(move || loading_registry.reload(&name))
+        .await
+        .map_err(|error| geojson::Error {
+            msg: error.to_string(),
+            status: 500,
+        })?
+        .map_err(|error| geojson::Error {
+            msg: error,
+            status: 500,
+        })?;
+    validate_profiles(&registry, &profiles);
+    Ok(web::Json(registry.status()))
+}
+
+/// get named or default graph; errors if unknown or not loaded yet
+fn get_graph(
+    registry: &GraphRegistry,
+    name: Option<&str>,
+) -> Result<Arc<LoadedGraph>, geojson::Error> {
+    let graph = match name {
+        Some(name) => {
+            if !registry.contains(name) {
+                return Err(geojson::Error {
+                    msg: format!("unknown graph '{}'", name),
+                    status: 404,
+                });
+            }
+            registry.get(name)
+        }
+        None => registry.default_graph(),
     };
+    graph.ok_or_else(|| geojson::Error {
+        msg: "graph is not loaded yet".to_string(),
+        status: 503,
+    })
+}
+
+/// get dijkstra of this worker for a graph and query type, a reloaded graph gets a new one
+fn get_engine<'a, E: 'static + Export>(
+    engines: &'a mut EngineMap<E>,
+    graph: &LoadedGraph,
+    query_type: QueryType,
+) -> &'a mut Box<dyn FindPath<E>> {
+    let new_engine = || prp_query::dijkstra::get(query_type, graph.data.nodes.len(), E::new());
+    let engine = engines
+        .entry((graph.config.name.clone(), query_type))
+        .or_insert_with(|| (graph.generation, new_engine()));
+    if engine.0 != graph.generation {
+        *engine = (graph.generation, new_engine());
+    }
+    &mut engine.1
+}
 
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
     std::env::set_var("RUST_LOG", "info");
     env_logger::init();
 
-    let amount_nodes = data.nodes.len();
-    let dim = data.edge_costs.len() / data.edges.len();
+    let (graph_configs, port, watch_interval, profile_file) = get_arguments();
 
-    sort_edges::sort_edges(query_type, &mut data);
+    let registry = web::Data::new(GraphRegistry::new(graph_configs));
+    let monitoring = web::Data::new(Monitoring::new());
+    let elicitations = web::Data::new(ElicitationStore::new());
+    let profiles = match ProfileStore::open(&profile_file) {
+        Ok(profiles) => web::Data::new(profiles),
+        Err(error) => panic!("error while reading profile-file: {}", error),
+    };
 
-    let graph = Graph::new(
-        data.edges,
-        data.edge_costs,
-        data.up_offset,
-        data.down_offset,
-        data.down_index,
-        dim,
-    );
-    let data_ref = web::Data::new(WebData {
-        nodes: data.nodes,
-        mlp_levels: data.mlp_levels,
-        graph,
-        grid_offset: data.grid_offset,
-        grid: data.grid,
-        grid_bounds: data.grid_bounds,
-        metrics: data.metrics,
+    // load graphs in the background, so health-checks get answered while loading
+    let loading_registry = registry.clone();
+    let loading_profiles = profiles.clone();
+    std::thread::spawn(move || {
+        for (name, result) in loading_registry.load_all() {
+            match result {
+                Ok(()) => info!("graph '{}' loaded", name),
+                Err(error) => error!("{}", error),
+            }
+        }
+        validate_profiles(&loading_registry, &loading_profiles);
+        if watch_interval == 0 {
+            return;
+        }
+        // reload bin-files, that changed on disk
+        loop {
+            std::thread::sleep(Duration::from_secs(watch_interval));
+            let results = loading_registry.reload_changed();
+            for (name, result) in &results {
+                match result {
+                    Ok(()) => info!("graph '{}' reloaded", name),
+                    Err(error) => error!("{}", error),
+                }
+            }
+            if !results.is_empty() {
+                validate_profiles(&loading_registry, &loading_profiles);
+            }
+        }
     });
 
     // check for static-html folder
@@ -203,29 +1129,103 @@ async fn main() -> std::io::Result<()> {
 
     // start webserver
     println!("Starting server at: http://localhost:{}", port);
-    HttpServer::new(move || {
-        // initialize thread-local dijkstra
-        let dijkstra = web::Data::new(RefCell::new(prp_query::dijkstra::get(
-            query_type,
-            amount_nodes,
-            NoOp::new(),
-        )));
+    let server_registry = registry.clone();
+    let server = HttpServer::new(move || {
+        // initialize thread-local dijkstras
+        let engines: web::Data<Engines> = web::Data::new(RefCell::new(HashMap::new()));
+        let debug_engines: web::Data<DebugEngines> = web::Data::new(RefCell::new(HashMap::new()));
+        let error_monitoring = monitoring.clone();
         App::new()
             .wrap(middleware::Logger::default())
+            // count failed api requests by route and status
+            .wrap_fn(move |request, service| {
+                let response = service.call(request);
+                let monitoring = error_monitoring.clone();
+                async move {
+                    let response = response.await?;
+                    let status = response.status();
+                    // static files have an empty route, so missing files like favicon.ico are skipped
+                    match response.request().match_pattern() {
+                        Some(route)
+                            if !route.is_empty()
+                                && (status.is_client_error() || status.is_server_error()) =>
+                        {
+                            monitoring.record_error(&route, status.as_u16())
+                        }
+                        _ => {}
+                    }
+                    Ok(response)
+                }
+            })
             .app_data(web::JsonConfig::default().limit(1024))
-            .app_data(data_ref.clone())
-            .app_data(dijkstra)
+            .app_data(server_registry.clone())
+            .app_data(monitoring.clone())
+            .app_data(profiles.clone())
+            .app_data(elicitations.clone())
+            .app_data(engines)
+            .app_data(debug_engines)
             .service(shortest_path)
+            .service(graph_shortest_path)
             .service(metrics)
+            .service(graph_metrics)
+            .service(info)
+            .service(graph_info)
+            .service(nearest)
+            .service(graph_nearest)
+            .service(prometheus)
+            .service(search_space)
+            .service(graph_search_space)
+            .service(all_algorithms)
+            .service(graph_all_algorithms)
+            .service(tile)
+            .service(graph_tile)
+            .service(graphs)
+            .service(health)
+            .service(ready)
+            .service(start_elicitation)
+            .service(graph_start_elicitation)
+            .service(answer_elicitation)
+            .service(list_profiles)
+            .service(create_profile)
+            .service(get_profile)
+            .service(update_profile)
+            .service(delete_profile)
+            .service(reload_all)
+            .service(reload_graph)
             .service(actix_files::Files::new("/", html_path).index_file("index.html"))
     })
     .bind(format!("localhost:{}", port))
     .expect("Can not bind to port")
-    .run()
-    .await
+    .disable_signals()
+    .run();
+
+    // cancel running queries, so the workers stop without waiting for them
+    let (signal_registry, signal_handle) = (registry.clone(), server.handle());
+    rt::spawn(async move {
+        let _ = rt::signal::ctrl_c().await;
+        shutdown(signal_registry, signal_handle).await;
+    });
+    #[cfg(unix)]
+    {
+        let (signal_registry, signal_handle) = (registry.clone(), server.handle());
+        let mut terminate = rt::signal::unix::signal(rt::signal::unix::SignalKind::terminate())
+            .expect("Can not listen for SIGTERM");
+        rt::spawn(async move {
+            terminate.recv().await;
+            shutdown(signal_registry, signal_handle).await;
+        });
+    }
+    server.await
 }
 
-fn get_arguments() -> (String, String, QueryType) {
+/// abort running queries, then stop the server gracefully
+async fn shutdown(registry: web::Data<GraphRegistry>, handle: ServerHandle) {
+    info!("shutting down, cancelling running queries");
+    registry.shutdown();
+    handle.stop(true).await;
+}
+
+fn get_arguments() -> (Vec<GraphConfig>, String, u64, String) {
     let matches = clap::Command::new("prp_web")
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
@@ -236,7 +1236,15 @@ fn get_arguments() -> (String, String, QueryType) {
                 .num_args(1)
                 .short('f')
                 .long("file")
-                .required(true),
+                .conflicts_with("config")
+                .required_unless_present("config"),
+        )
+        .arg(
+            clap::Arg::new("config")
+                .help("json-file listing multiple graphs (name, file, query_type, default_alpha)")
+                .num_args(1)
+                .short('c')
+                .long("config"),
         )
         .arg(
             clap::Arg::new("port")
@@ -252,17 +1260,84 @@ fn get_arguments() -> (String, String, QueryType) {
                 .num_args(1)
                 .short('q')
                 .long("query")
-                .required(true)
+                .conflicts_with("config")
+                .required_unless_present("config")
                 .value_parser(clap::value_parser!(QueryType)),
         )
+        .arg(
+            clap::Arg::new("timeout")
+                .help("milliseconds until a query gets aborted (set per graph in a config)")
+                .num_args(1)
+                .short('t')
+                .long("timeout")
+                .default_value("10000")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            clap::Arg::new("max-settled-nodes")
+                .help("settled nodes until a query gets aborted (set per graph in a config)")
+                .num_args(1)
+                .long("max-settled-nodes")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            clap::Arg::new("snap-main-component")
+                .help(
+                    "snap clicked points only to nodes of the largest strongly connected component",
+                )
+                .long("snap-main-component")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("max-snap-distance")
+                .help(
+                    "meters a clicked point may be away from the graph (set per graph in a config)",
+                )
+                .num_args(1)
+                .long("max-snap-distance")
+                .value_parser(clap::value_parser!(f64)),
+        )
+        .arg(
+            clap::Arg::new("watch-interval")
+                .help("seconds between checks for changed bin-files (0 disables reloading)")
+                .num_args(1)
+                .short('w')
+                .long("watch-interval")
+                .default_value("10")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            clap::Arg::new("profiles")
+                .help("json-file storing the preference profiles")
+                .num_args(1)
+                .long("profiles")
+                .default_value("profiles.json"),
+        )
         .get_matches();
-    let query_type = matches
-        .get_one::<QueryType>("query")
-        .expect("`query` is required");
+
+    let graph_configs = match matches.get_one::<String>("config") {
+        Some(config_file) => match ServerConfig::read_file(config_file) {
+            Ok(config) => config.graphs,
+            Err(error) => panic!("error while reading config-file: {:?}", error),
+        },
+        None => vec![GraphConfig {
+            name: "default".to_string(),
+            file: matches.get_one::<String>("fmi-file").unwrap().to_string(),
+            query_type: *matches
+                .get_one::<QueryType>("query")
+                .expect("`query` is required"),
+            default_alpha: None,
+            timeout_ms: *matches.get_one::<u64>("timeout").unwrap(),
+            max_settled_nodes: matches.get_one::<usize>("max-settled-nodes").copied(),
+            snap_to_main_component: matches.get_flag("snap-main-component"),
+            max_snap_distance: matches.get_one::<f64>("max-snap-distance").copied(),
+        }],
+    };
 
     (
-        matches.get_one::<String>("fmi-file").unwrap().to_string(),
+        graph_configs,
         matches.get_one::<String>("port").unwrap().to_string(),
-        *query_type,
+        *matches.get_one::<u64>("watch-interval").unwrap(),
+        matches.get_one::<String>("profiles").unwrap().to_string(),
     )
 }
@@ -1,11 +1,77 @@
 use super::*;
 use bincode::deserialize_from;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Read};
 
 pub fn read_file(fmi_file: &str) -> Result<BinFile, Box<dyn std::error::Error>> {
     let file = File::open(fmi_file)?;
     let mut reader = BufReader::new(file);
+    check_header(&mut reader)?;
     let data: BinFile = deserialize_from(&mut reader)?;
     Ok(data)
 }
+
+/// bin-files of older versions can not be read, they fail before the layout is guessed wrong
+fn check_header<R: Read>(reader: &mut R) -> Result<(), Box<dyn std::error::Error>> {
+    let mut magic = [0u8; 8];
+    if reader.read_exact(&mut magic).is_err() || magic != BIN_FILE_MAGIC {
+        return Err(
+            "bin-file has no format version, it was written by an older prp_pre. \
+             convert it with prp_upgrade or run prp_pre again"
+                .into(),
+        );
+    }
+    let version: u32 = deserialize_from(&mut *reader)?;
+    if version != BIN_FILE_VERSION {
+        return Err(format!(
+            "bin-file has format version {}, but version {} is expected. run prp_pre again",
+            version, BIN_FILE_VERSION
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// read bin-file, the edge orders of all query-types are part of it
+pub fn load_web_data(fmi_file: &str) -> Result<WebData, Box<dyn std::error::Error>> {
+    let data = read_file(fmi_file)?;
+
+    let dim = data.edge_costs.len() / data.edges.len();
+
+    let graph = Graph::new(
+        data.edges,
+        data.edge_costs,
+        data.up_offset,
+        data.down_offset,
+        data.down_index,
+        dim,
+    )
+    .with_edge_orders(data.edge_orders);
+    Ok(WebData {
+        nodes: data.nodes,
+        mlp_levels: data.mlp_levels,
+        graph,
+        grid_offset: data.grid_offset,
+        grid: data.grid,
+        grid_bounds: data.grid_bounds,
+        metrics: data.metrics,
+        components: Components::new(data.strong_components, data.weak_components),
+    })
+}
+
+#[test]
+fn header() {
+    let mut current = BIN_FILE_MAGIC.to_vec();
+    current.extend_from_slice(&BIN_FILE_VERSION.to_le_bytes());
+    assert!(check_header(&mut current.as_slice()).is_ok());
+
+    // old bin-files start with the amount of nodes
+    let old = 36u64.to_le_bytes();
+    let error = check_header(&mut &old[..]).unwrap_err().to_string();
+    assert!(error.contains("prp_upgrade"));
+
+    let mut newer = BIN_FILE_MAGIC.to_vec();
+    newer.extend_from_slice(&(BIN_FILE_VERSION + 1).to_le_bytes());
+    let error = check_header(&mut newer.as_slice()).unwrap_err().to_string();
+    assert!(error.contains(&format!("format version {}", BIN_FILE_VERSION + 1)));
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+/// connected components of the nodes, computed while pre-processing.
+/// strong ids are in reverse topological order, so every edge leads to a smaller or equal id
+#[derive(Clone, Debug, Default)]
+pub struct Components {
+    strong: Vec<ComponentId>,
+    weak: Vec<ComponentId>,
+    /// largest strongly connected component
+    main: Option<ComponentId>,
+}
+
+impl Components {
+    pub fn new(strong: Vec<ComponentId>, weak: Vec<ComponentId>) -> Self {
+        let mut sizes = vec![0; strong.iter().max().map_or(0, |max| max + 1)];
+        for component in &strong {
+            sizes[*component] += 1;
+        }
+        let main = sizes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, size)| **size)
+            .map(|(component, _)| component);
+        Components { strong, weak, main }
+    }
+
+    /// false if there is certainly no path from one node to the other
+    pub fn may_connect(&self, from: NodeId, to: NodeId) -> bool {
+        if self.strong.is_empty() {
+            return true;
+        }
+        self.weak[from] == self.weak[to] && self.strong[from] >= self.strong[to]
+    }
+
+    pub fn is_in_main(&self, node: NodeId) -> bool {
+        match self.main {
+            Some(main) => self.strong[node] == main,
+            None => true,
+        }
+    }
+}
+
+#[test]
+fn component_precheck() {
+    // 0 <-> 1 -> 2 <-> 3 <-> 4, 5 alone
+    let components = Components::new(vec![1, 1, 0, 0, 0, 2], vec![0, 0, 0, 0, 0, 1]);
+    assert!(components.may_connect(0, 1));
+    assert!(components.may_connect(1, 3));
+    assert!(!components.may_connect(3, 1));
+    assert!(!components.may_connect(0, 5));
+    assert!(components.is_in_main(4));
+    assert!(!components.is_in_main(0));
+    assert!(Components::default().may_connect(0, 5));
+}
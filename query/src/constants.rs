@@ -5,6 +5,7 @@ pub type Rank = usize;
 pub type PartitionId = usize;
 pub type Level = usize;
 pub type GridId = usize;
+pub type ComponentId = usize;
 pub type Angle = f64;
 
 #[allow(dead_code)]
@@ -15,3 +16,6 @@ pub const INVALID_EDGE: NodeId = usize::MAX;
 pub const COST_MAX: Cost = f64::MAX;
 pub const INVALID_RANK: Rank = usize::MAX;
 pub const INVALID_LEVEL: Level = usize::MAX;
+
+// the bin-files are written by prp_pre, so it defines their header
+pub use prp_pre::{BIN_FILE_MAGIC, BIN_FILE_VERSION};
@@ -1,5 +1,11 @@
 use super::*;
-use query_export::Export;
+use query_export::{Export, Phase};
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
 pub mod bidirectional;
 pub mod normal;
@@ -7,21 +13,260 @@ pub mod pch;
 pub mod pcrp;
 pub mod prp;
 
-pub trait FindPath<E: Export> {
+/// per-request options of a query
+#[derive(Clone, Debug)]
+pub struct QueryOptions {
+    /// only paths cheaper than this are searched, without one the result is "no path".
+    /// unlike `QueryLimits::max_cost` this is part of the question, not a budget.
+    /// if both are set, the lower one takes effect
+    pub cost_limit: Option<Cost>,
+    /// edges, that must not be used. see `forbid_edges` for also excluding their shortcuts
+    pub forbidden_edges: HashSet<EdgeId>,
+    /// resolve shortcuts into original edges
+    pub unpack_path: bool,
+    /// sum up every metric along the path
+    pub metric_costs: bool,
+    pub limits: QueryLimits,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        QueryOptions {
+            cost_limit: None,
+            forbidden_edges: HashSet::new(),
+            unpack_path: true,
+            metric_costs: false,
+            limits: QueryLimits::default(),
+        }
+    }
+}
+
+impl QueryOptions {
+    /// forbid edges and every shortcut containing one of them
+    pub fn forbid_edges(mut self, edges: &[EdgeId], graph: &Graph) -> Self {
+        self.forbidden_edges.extend(edges);
+        // shortcuts of shortcuts need multiple passes
+        loop {
+            let amount_forbidden = self.forbidden_edges.len();
+            for (edge_id, edge) in graph.edges.iter().enumerate() {
+                if let Some((first, second)) = edge.contracted_edges {
+                    if self.forbidden_edges.contains(&first)
+                        || self.forbidden_edges.contains(&second)
+                    {
+                        self.forbidden_edges.insert(edge_id);
+                    }
+                }
+            }
+            if amount_forbidden == self.forbidden_edges.len() {
+                return self;
+            }
+        }
+    }
+
+    pub fn is_forbidden(&self, edge_id: EdgeId) -> bool {
+        !self.forbidden_edges.is_empty() && self.forbidden_edges.contains(&edge_id)
+    }
+
+    /// initial bound of the best cost
+    pub fn max_cost(&self) -> Cost {
+        self.cost_limit.unwrap_or(COST_MAX)
+    }
+}
+
+/// budgets of a query, exceeding one aborts the query instead of returning no path
+#[derive(Clone, Debug, Default)]
+pub struct QueryLimits {
+    pub max_settled_nodes: Option<usize>,
+    /// abort with `AbortReason::MaxCost` when nodes beyond this cost get settled.
+    /// unlike `QueryOptions::cost_limit` hitting it is an error and not "no path".
+    /// if both are set, the lower one takes effect
+    pub max_cost: Option<Cost>,
+    pub deadline: Option<Instant>,
+    pub cancel: Option<CancelToken>,
+}
+
+/// shared flag for aborting a running query from another thread
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// the limit that stopped a query
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbortReason {
+    SettledNodes(usize),
+    MaxCost(Cost),
+    Deadline,
+    Cancelled,
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbortReason::SettledNodes(limit) => {
+                write!(f, "query aborted after settling {} nodes", limit)
+            }
+            AbortReason::MaxCost(limit) => write!(f, "query aborted at cost {}", limit),
+            AbortReason::Deadline => write!(f, "query aborted at its deadline"),
+            AbortReason::Cancelled => write!(f, "query cancelled"),
+        }
+    }
+}
+
+/// the clock and the cancel flag are only checked every few settled nodes
+const LIMIT_CHECK_INTERVAL: usize = 256;
+
+/// limits of a single running query
+struct Budget<'a> {
+    limits: &'a QueryLimits,
+    settled_nodes: usize,
+}
+
+impl<'a> Budget<'a> {
+    fn new(limits: &'a QueryLimits) -> Self {
+        Budget {
+            limits,
+            settled_nodes: 0,
+        }
+    }
+
+    /// count a settled node and check every limit
+    fn settle(&mut self, cost: Cost) -> Result<(), AbortReason> {
+        self.settled_nodes += 1;
+        if let Some(limit) = self.limits.max_settled_nodes {
+            if self.settled_nodes > limit {
+                return Err(AbortReason::SettledNodes(limit));
+            }
+        }
+        if let Some(limit) = self.limits.max_cost {
+            if cost > limit {
+                return Err(AbortReason::MaxCost(limit));
+            }
+        }
+        if self.settled_nodes % LIMIT_CHECK_INTERVAL == 1 {
+            if let Some(deadline) = self.limits.deadline {
+                if Instant::now() >= deadline {
+                    return Err(AbortReason::Deadline);
+                }
+            }
+            if let Some(cancel) = &self.limits.cancel {
+                if cancel.is_cancelled() {
+                    return Err(AbortReason::Cancelled);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// a single query borrowing all graph data it needs
+#[derive(Clone, Debug)]
+pub struct QueryRequest<'a> {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub alpha: &'a [f64],
+    pub graph: &'a Graph,
+    pub nodes: &'a [Node],
+    pub mlp_levels: &'a [usize],
+    /// skips searching for nodes without connection
+    pub components: Option<&'a Components>,
+    pub options: QueryOptions,
+}
+
+impl<'a> QueryRequest<'a> {
+    pub fn new(
+        from: NodeId,
+        to: NodeId,
+        alpha: &'a [f64],
+        graph: &'a Graph,
+        nodes: &'a [Node],
+        mlp_levels: &'a [usize],
+    ) -> Self {
+        QueryRequest {
+            from,
+            to,
+            alpha,
+            graph,
+            nodes,
+            mlp_levels,
+            components: None,
+            options: QueryOptions::default(),
+        }
+    }
+
+    /// query on the graph of the webserver
+    pub fn on(data: &'a WebData, from: NodeId, to: NodeId, alpha: &'a [f64]) -> Self {
+        QueryRequest::new(from, to, alpha, &data.graph, &data.nodes, &data.mlp_levels)
+            .with_components(&data.components)
+    }
+
+    pub fn with_components(mut self, components: &'a Components) -> Self {
+        self.components = Some(components);
+        self
+    }
+
+    /// false if the components show, that there is no path
+    pub fn may_connect(&self) -> bool {
+        match self.components {
+            Some(components) => components.may_connect(self.from, self.to),
+            None => true,
+        }
+    }
+
+    pub fn with_options(mut self, options: QueryOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryResult {
+    /// edge-ids from source to target, containing shortcuts if not unpacked
+    pub path: Vec<EdgeId>,
+    pub cost: Cost,
+    /// cost of every metric along the path, if requested
+    pub metric_costs: Option<Vec<Cost>>,
+}
+
+impl QueryResult {
+    pub fn new(path: Vec<EdgeId>, cost: Cost, request: &QueryRequest) -> Self {
+        let metric_costs = if request.options.metric_costs {
+            Some(request.graph.get_path_costs(&path))
+        } else {
+            None
+        };
+        QueryResult {
+            path,
+            cost,
+            metric_costs,
+        }
+    }
+}
+
+/// dijkstras only own their buffers, so they can be moved to other threads,
+/// e.g. for running batches of queries without holding the GIL of python
+pub trait FindPath<E: Export>: Send {
     fn new(amount_nodes: usize, exporter: E) -> Self
     where
         Self: Sized;
     fn reset_state(&mut self);
     fn get_query_export(&self) -> &E;
-    fn find_path(
-        &mut self,
-        from: NodeId,
-        to: NodeId,
-        alpha: Vec<f64>,
-        graph: &Graph,
-        nodes: &[Node],
-        _mlp_levels: &[usize],
-    ) -> Option<(Vec<NodeId>, Cost)>;
+    /// for phases outside of the search, which the caller times like snapping or the geometry
+    fn get_query_export_mut(&mut self) -> &mut E;
+    /// None if there is no path, an error if a limit of the request was hit
+    fn find_path(&mut self, request: &QueryRequest) -> Result<Option<QueryResult>, AbortReason>;
 }
 
 pub fn get<E: 'static + Export>(
@@ -40,3 +285,140 @@ pub fn get<E: 'static + Export>(
         QueryType::Prp => Box::new(dijkstra::prp::Dijkstra::new(amount_nodes, exporter)),
     }
 }
+
+#[test]
+fn query_options() {
+    use query_export::Counter;
+
+    // 0 -> 1 -> 2 with a shortcut 0 -> 2 over both and a direct, expensive edge 0 -> 2
+    let edge = |from, to, contracted_edges| Edge {
+        from,
+        to,
+        level: None,
+        contracted_edges,
+        old_id: None,
+    };
+    let graph = Graph::new(
+        vec![
+            edge(0, 1, None),
+            edge(0, 2, None),
+            edge(0, 2, Some((0, 3))),
+            edge(1, 2, None),
+        ],
+        vec![1.0, 5.0, 2.0, 1.0],
+        vec![0, 3, 4, 4],
+        vec![0, 0, 1, 4],
+        vec![0, 1, 2, 3],
+        1,
+    );
+    let nodes: Vec<Node> = (0..3)
+        .map(|rank| Node {
+            latitude: 0.0,
+            longitude: 0.0,
+            rank,
+            partition: 0,
+        })
+        .collect();
+    let alpha = [1.0];
+    let mut dijkstra = get(QueryType::Normal, nodes.len(), Counter::new());
+
+    let request = QueryRequest::new(0, 2, &alpha, &graph, &nodes, &[]);
+    let result = dijkstra.find_path(&request).unwrap().unwrap();
+    assert_eq!(result.path, vec![0, 3]);
+    assert_eq!(result.cost, 2.0);
+    assert_eq!(result.metric_costs, None);
+
+    // forbidding an edge also forbids the shortcut containing it
+    let options = QueryOptions {
+        metric_costs: true,
+        ..QueryOptions::default()
+    }
+    .forbid_edges(&[3], &graph);
+    assert!(options.is_forbidden(2));
+    let request = request.with_options(options);
+    let result = dijkstra.find_path(&request).unwrap().unwrap();
+    assert_eq!(result.path, vec![1]);
+    assert_eq!(result.metric_costs, Some(vec![5.0]));
+
+    let mut options = request.options.clone();
+    options.cost_limit = Some(4.0);
+    assert_eq!(dijkstra.find_path(&request.with_options(options)), Ok(None));
+}
+
+#[test]
+fn query_limits() {
+    use query_export::Counter;
+
+    // line of 10 nodes
+    let edges: Vec<Edge> = (0..9)
+        .map(|node| Edge {
+            from: node,
+            to: node + 1,
+            level: None,
+            contracted_edges: None,
+            old_id: None,
+        })
+        .collect();
+    let mut up_offset: Vec<EdgeId> = (0..10).collect();
+    up_offset.push(9);
+    let mut down_offset = vec![0];
+    down_offset.extend(0..10);
+    let graph = Graph::new(
+        edges,
+        vec![1.0; 9],
+        up_offset,
+        down_offset,
+        (0..9).collect(),
+        1,
+    );
+    let nodes: Vec<Node> = (0..10)
+        .map(|rank| Node {
+            latitude: 0.0,
+            longitude: 0.0,
+            rank,
+            partition: 0,
+        })
+        .collect();
+    let alpha = [1.0];
+    let request = QueryRequest::new(0, 9, &alpha, &graph, &nodes, &[]);
+    let with_limits = |limits: QueryLimits| {
+        request.clone().with_options(QueryOptions {
+            limits,
+            ..QueryOptions::default()
+        })
+    };
+
+    for query_type in &[QueryType::Normal, QueryType::Bi] {
+        let mut dijkstra = get(*query_type, nodes.len(), Counter::new());
+        assert_eq!(dijkstra.find_path(&request).unwrap().unwrap().cost, 9.0);
+
+        let limited = with_limits(QueryLimits {
+            max_settled_nodes: Some(3),
+            ..QueryLimits::default()
+        });
+        assert_eq!(
+            dijkstra.find_path(&limited),
+            Err(AbortReason::SettledNodes(3))
+        );
+
+        let limited = with_limits(QueryLimits {
+            max_cost: Some(2.5),
+            ..QueryLimits::default()
+        });
+        assert_eq!(dijkstra.find_path(&limited), Err(AbortReason::MaxCost(2.5)));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let cancelled = with_limits(QueryLimits {
+            cancel: Some(cancel),
+            ..QueryLimits::default()
+        });
+        assert_eq!(dijkstra.find_path(&cancelled), Err(AbortReason::Cancelled));
+
+        let late = with_limits(QueryLimits {
+            deadline: Some(Instant::now()),
+            ..QueryLimits::default()
+        });
+        assert_eq!(dijkstra.find_path(&late), Err(AbortReason::Deadline));
+    }
+}
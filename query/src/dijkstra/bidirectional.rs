@@ -49,21 +49,31 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
     fn get_query_export(&self) -> &E {
         &self.exporter
     }
+    fn get_query_export_mut(&mut self) -> &mut E {
+        &mut self.exporter
+    }
 
     /// return shortest path of edges
-    fn find_path(
-        &mut self,
-        from: NodeId,
-        to: NodeId,
-        alpha: Vec<f64>,
-        graph: &Graph,
-        nodes: &[Node],
-        _mlp_levels: &[usize],
-    ) -> Option<(Vec<NodeId>, Cost)> {
+    fn find_path(&mut self, request: &QueryRequest) -> Result<Option<QueryResult>, AbortReason> {
+        let QueryRequest {
+            from,
+            to,
+            alpha,
+            graph,
+            nodes,
+            ref options,
+            ..
+        } = *request;
         self.reset_state();
+        let mut budget = Budget::new(&options.limits);
+
+        // start and end lie in components without connection
+        if !request.may_connect() {
+            return Ok(None);
+        }
 
         if from == to {
-            return Some((vec![], 0.0));
+            return Ok(Some(QueryResult::new(vec![], 0.0, request)));
         }
 
         self.visited_up.set_valid(from);
@@ -74,12 +84,15 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
         self.heap_up.push(MinHeapItem::new(from, 0.0, None));
         self.heap_down.push(MinHeapItem::new(to, 0.0, None));
 
-        let mut best_cost = COST_MAX;
+        // paths above the cost limit are never accepted
+        let mut best_cost = options.max_cost();
         let mut meeting_node = None;
 
         // function pointers for only having one single dijkstra
-        let get_up_edge_ids: fn(&Graph, NodeId) -> Vec<EdgeId> = Graph::get_up_edge_ids;
-        let get_down_edge_ids: fn(&Graph, NodeId) -> Vec<EdgeId> = Graph::get_down_edge_ids;
+        let get_up_edge_ids: fn(&Graph, QueryType, NodeId) -> EdgeIds<'_> =
+            Graph::get_ordered_up_edge_ids;
+        let get_down_edge_ids: fn(&Graph, QueryType, NodeId) -> EdgeIds<'_> =
+            Graph::get_ordered_down_edge_ids;
         let get_to: fn(&Edge) -> NodeId = Edge::get_to;
         let get_from: fn(&Edge) -> NodeId = Edge::get_from;
 
@@ -147,10 +160,12 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 continue;
             }
 
+            budget.settle(cost)?;
+
             exporter.visited_node(node);
             exporter.visited_edge(prev_edge);
 
-            for edge_id in get_edges(graph, node) {
+            for edge_id in get_edges(graph, QueryType::Bi, node) {
                 let edge = graph.get_edge(edge_id);
 
                 // skip edges, that are shortcuts
@@ -158,11 +173,15 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                     break;
                 }
 
+                if options.is_forbidden(edge_id) {
+                    continue;
+                }
+
                 let next = walk(&edge);
 
                 exporter.relaxed_edge();
 
-                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), &alpha);
+                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), alpha);
 
                 if !visited.is_valid(next) || alt < dist[next].0 {
                     heap.push(MinHeapItem::new(next, alt, Some(edge_id)));
@@ -181,9 +200,10 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 }
             }
         }
-        meeting_node.map(|meet_node| {
-            self.resolve_path(meet_node, best_cost, nodes[meet_node].rank, &graph.edges)
-        })
+        Ok(meeting_node.map(|meet_node| {
+            let path = self.resolve_path(meet_node, nodes[meet_node].rank, &graph.edges);
+            QueryResult::new(path, best_cost, request)
+        }))
     }
 }
 impl<E: Export> Dijkstra<E> {
@@ -191,10 +211,9 @@ impl<E: Export> Dijkstra<E> {
     fn resolve_path(
         &self,
         meeting_node: NodeId,
-        cost: Cost,
         meeting_rank: Rank,
         edges: &[Edge],
-    ) -> (Vec<NodeId>, Cost) {
+    ) -> Vec<EdgeId> {
         assert!(self.visited_up.is_valid(meeting_node));
         assert!(self.visited_down.is_valid(meeting_node));
 
@@ -212,6 +231,6 @@ impl<E: Export> Dijkstra<E> {
             path.push(prev_edge);
             down_edge = self.dist_down[edges[prev_edge].to];
         }
-        (path, cost)
+        path
     }
 }
@@ -34,22 +34,34 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
     fn reset_state(&mut self) {
         self.heap.clear();
         self.visited.invalidate_all();
+        self.exporter.reset();
     }
     fn get_query_export(&self) -> &E {
         &self.exporter
     }
+    fn get_query_export_mut(&mut self) -> &mut E {
+        &mut self.exporter
+    }
 
     /// return path of edges(!) from source to target not path of nodes!
-    fn find_path(
-        &mut self,
-        from: NodeId,
-        to: NodeId,
-        alpha: Vec<f64>,
-        graph: &Graph,
-        _nodes: &[Node],
-        _mlp_levels: &[usize],
-    ) -> Option<(Vec<NodeId>, Cost)> {
+    fn find_path(&mut self, request: &QueryRequest) -> Result<Option<QueryResult>, AbortReason> {
+        let QueryRequest {
+            from,
+            to,
+            alpha,
+            graph,
+            ref options,
+            ..
+        } = *request;
         self.reset_state();
+        let mut budget = Budget::new(&options.limits);
+
+        // start and end lie in components without connection
+        if !request.may_connect() {
+            return Ok(None);
+        }
+
+        let max_cost = options.max_cost();
 
         self.heap.push(MinHeapItem::new(from, 0.0, None));
 
@@ -65,19 +77,26 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
             if self.visited.is_valid(node) && cost > self.dist[node].0 {
                 continue;
             }
+            // all remaining nodes are too expensive
+            if cost >= max_cost {
+                break;
+            }
 
             self.visited.set_valid(node);
             self.dist[node] = (cost, prev_edge);
 
+            budget.settle(cost)?;
+
             self.exporter.visited_node(node);
             self.exporter.visited_edge(prev_edge);
 
             // found end
             if node == to {
-                return Some(self.resolve_path(to, &graph.edges));
+                let (path, cost) = self.resolve_path(to, &graph.edges);
+                return Ok(Some(QueryResult::new(path, cost, request)));
             }
 
-            for edge_id in graph.get_up_edge_ids(node) {
+            for edge_id in graph.get_ordered_up_edge_ids(QueryType::Normal, node) {
                 let new_edge = graph.get_edge(edge_id);
 
                 // skip edges, that are shortcuts
@@ -89,16 +108,20 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                     }
                 }
 
+                if options.is_forbidden(edge_id) {
+                    continue;
+                }
+
                 self.exporter.relaxed_edge();
 
-                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), &alpha);
+                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), alpha);
                 if !self.visited.is_valid(new_edge.to) || alt < self.dist[new_edge.to].0 {
                     self.heap
                         .push(MinHeapItem::new(new_edge.to, alt, Some(edge_id)));
                 }
             }
         }
-        None
+        Ok(None)
     }
 }
 impl<E: Export> Dijkstra<E> {
@@ -57,21 +57,32 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
     fn get_query_export(&self) -> &E {
         &self.exporter
     }
+    fn get_query_export_mut(&mut self) -> &mut E {
+        &mut self.exporter
+    }
 
     /// return shortest path of nodes
-    fn find_path(
-        &mut self,
-        from: NodeId,
-        to: NodeId,
-        alpha: Vec<f64>,
-        graph: &Graph,
-        nodes: &[Node],
-        mlp_levels: &[usize],
-    ) -> Option<(Vec<NodeId>, Cost)> {
+    fn find_path(&mut self, request: &QueryRequest) -> Result<Option<QueryResult>, AbortReason> {
+        let QueryRequest {
+            from,
+            to,
+            alpha,
+            graph,
+            nodes,
+            mlp_levels,
+            ref options,
+            ..
+        } = *request;
         self.reset_state();
+        let mut budget = Budget::new(&options.limits);
+
+        // start and end lie in components without connection
+        if !request.may_connect() {
+            return Ok(None);
+        }
 
         if from == to {
-            return Some((vec![], 0.0));
+            return Ok(Some(QueryResult::new(vec![], 0.0, request)));
         }
 
         self.visited_up.set_valid(from);
@@ -82,18 +93,22 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
         self.heap_pch_up.push(MinHeapItem::new(from, 0.0, None));
         self.heap_pch_down.push(MinHeapItem::new(to, 0.0, None));
 
-        let mut best_cost = COST_MAX;
+        // paths above the cost limit are never accepted
+        let mut best_cost = options.max_cost();
         let mut meeting_node = None;
 
         let from_partitions = mlp_helper::get_node_partitions(from, nodes, mlp_levels);
         let to_partitions = mlp_helper::get_node_partitions(to, nodes, mlp_levels);
 
         // function pointers for only having one single dijkstra
-        let get_up_edge_ids: fn(&Graph, NodeId) -> Vec<EdgeId> = Graph::get_up_edge_ids;
-        let get_down_edge_ids: fn(&Graph, NodeId) -> Vec<EdgeId> = Graph::get_down_edge_ids;
+        let get_up_edge_ids: fn(&Graph, QueryType, NodeId) -> EdgeIds<'_> =
+            Graph::get_ordered_up_edge_ids;
+        let get_down_edge_ids: fn(&Graph, QueryType, NodeId) -> EdgeIds<'_> =
+            Graph::get_ordered_down_edge_ids;
         let get_to: fn(&Edge) -> NodeId = Edge::get_to;
         let get_from: fn(&Edge) -> NodeId = Edge::get_from;
 
+        let pch_time = Instant::now();
         'outer: while let Some((
             MinHeapItem {
                 node,
@@ -165,10 +180,12 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 continue;
             }
 
+            budget.settle(cost)?;
+
             exporter.visited_node(node);
             exporter.visited_edge(prev_edge);
 
-            for edge_id in get_edges(graph, node) {
+            for edge_id in get_edges(graph, QueryType::Prp, node) {
                 let edge = graph.get_edge(edge_id);
                 let next = walk(&edge);
 
@@ -184,9 +201,13 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                     continue;
                 }
 
+                if options.is_forbidden(edge_id) {
+                    continue;
+                }
+
                 exporter.relaxed_edge();
 
-                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), &alpha);
+                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), alpha);
 
                 if !visited.is_valid(next) || alt < dist[next].0 {
                     if nodes[node].partition != nodes[next].partition {
@@ -211,7 +232,9 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 }
             }
         }
+        self.exporter.phase_finished(Phase::Pch, pch_time.elapsed());
 
+        let overlay_time = Instant::now();
         while let Some((
             MinHeapItem {
                 node,
@@ -276,6 +299,8 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 continue;
             }
 
+            budget.settle(cost)?;
+
             exporter.visited_node(node);
             exporter.visited_edge(prev_edge);
 
@@ -295,7 +320,7 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 ),
             );
 
-            for edge_id in get_edges(graph, node) {
+            for edge_id in get_edges(graph, QueryType::Prp, node) {
                 let edge = graph.get_edge(edge_id);
 
                 // skip edges, that are pch-shortcuts-resolutions from upper levels
@@ -309,9 +334,12 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
                 }
 
                 let next = walk(&edge);
+                if options.is_forbidden(edge_id) {
+                    continue;
+                }
                 exporter.relaxed_edge();
 
-                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), &alpha);
+                let alt = cost + costs_by_alpha(graph.get_edge_costs(edge_id), alpha);
 
                 if !visited.is_valid(next) || alt < dist[next].0 {
                     heap.push(MinHeapItem::new(next, alt, Some(edge_id)));
@@ -331,9 +359,21 @@ impl<E: Export> FindPath<E> for Dijkstra<E> {
             }
         }
 
-        meeting_node.map(|meet_node| {
-            self.resolve_path(meet_node, best_cost, nodes[meet_node].rank, &graph.edges)
-        })
+        self.exporter
+            .phase_finished(Phase::Overlay, overlay_time.elapsed());
+
+        let unpack_time = Instant::now();
+        let path = meeting_node.map(|meet_node| {
+            self.resolve_path(
+                meet_node,
+                nodes[meet_node].rank,
+                options.unpack_path,
+                &graph.edges,
+            )
+        });
+        self.exporter
+            .phase_finished(Phase::Unpack, unpack_time.elapsed());
+        Ok(path.map(|path| QueryResult::new(path, best_cost, request)))
     }
 }
 impl<E: Export> Dijkstra<E> {
@@ -341,10 +381,10 @@ impl<E: Export> Dijkstra<E> {
     fn resolve_path(
         &self,
         meeting_node: NodeId,
-        cost: Cost,
         meeting_rank: Rank,
+        unpack: bool,
         edges: &[Edge],
-    ) -> (Vec<NodeId>, Cost) {
+    ) -> Vec<EdgeId> {
         assert!(self.visited_up.is_valid(meeting_node));
         assert!(self.visited_down.is_valid(meeting_node));
 
@@ -354,18 +394,29 @@ impl<E: Export> Dijkstra<E> {
         let down_edge = self.dist_down[meeting_node];
 
         if let Some(prev_edge) = up_edge.1 {
-            self.walk_down(prev_edge, true, &mut path, edges);
+            self.walk_down(prev_edge, true, unpack, &mut path, edges);
         }
         path.reverse();
         if let Some(prev_edge) = down_edge.1 {
-            self.walk_down(prev_edge, false, &mut path, edges);
+            self.walk_down(prev_edge, false, unpack, &mut path, edges);
         }
-        (path, cost)
+        path
     }
 
     // walk shortcuts from meeting point to end
-    fn walk_down(&self, edge: EdgeId, is_upwards: bool, path: &mut Vec<NodeId>, edges: &[Edge]) {
-        resolve_edge(edge, path, is_upwards, edges);
+    fn walk_down(
+        &self,
+        edge: EdgeId,
+        is_upwards: bool,
+        unpack: bool,
+        path: &mut Vec<EdgeId>,
+        edges: &[Edge],
+    ) {
+        if unpack {
+            resolve_edge(edge, path, is_upwards, edges);
+        } else {
+            path.push(edge);
+        }
 
         let current_edge = &edges[edge];
 
@@ -375,7 +426,7 @@ impl<E: Export> Dijkstra<E> {
             self.dist_down[current_edge.to]
         };
         if let Some(child) = prev.1 {
-            self.walk_down(child, is_upwards, path, edges);
+            self.walk_down(child, is_upwards, unpack, path, edges);
         }
     }
 }
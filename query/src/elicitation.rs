@@ -0,0 +1,392 @@
+use super::*;
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+/// alphas approximating the simplex of possible preferences
+pub const DEFAULT_SAMPLES: usize = 2000;
+/// a session ends after this many answers at the latest
+pub const MAX_ROUNDS: usize = 6;
+/// a session ends, when all remaining alphas are this close to the estimate
+pub const MIN_SPREAD: f64 = 0.05;
+/// older sessions get dropped beyond this amount
+pub const MAX_SESSIONS: usize = 1000;
+
+/// deterministic xorshift generator, so sessions are reproducible
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // the state must never be 0
+        XorShift((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// uniform in (0, 1]
+    fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// alphas still consistent with the answers of a user.
+///
+/// choosing route `a` over route `b` means `alpha * cost(a) <= alpha * cost(b)`,
+/// so every answer cuts the simplex with the halfspace `alpha * (cost(a) - cost(b)) <= 0`.
+/// the region is represented by uniform samples of the simplex.
+#[derive(Clone, Debug)]
+pub struct PreferenceRegion {
+    dim: usize,
+    samples: Vec<Vec<f64>>,
+    constraints: Vec<Vec<f64>>,
+}
+
+impl PreferenceRegion {
+    pub fn new(dim: usize, amount_samples: usize, seed: u64) -> Self {
+        let mut random = XorShift::new(seed);
+        let mut samples = Vec::with_capacity(amount_samples + dim);
+        // corners of the simplex, so pure preferences stay reachable
+        for i in 0..dim {
+            let mut corner = vec![0.0; dim];
+            corner[i] = 1.0;
+            samples.push(corner);
+        }
+        // normalized exponential variables are uniform on the simplex
+        for _ in 0..amount_samples {
+            let sample: Vec<f64> = (0..dim).map(|_| -random.next_f64().ln()).collect();
+            let sum: f64 = sample.iter().sum();
+            samples.push(sample.iter().map(|x| x / sum).collect());
+        }
+        PreferenceRegion {
+            dim,
+            samples,
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn amount_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// the user preferred the route with `chosen` costs over the one with `rejected` costs
+    pub fn add_preference(&mut self, chosen: &[Cost], rejected: &[Cost]) {
+        assert_eq!(chosen.len(), self.dim);
+        assert_eq!(rejected.len(), self.dim);
+        let normal: Vec<f64> = chosen.iter().zip(rejected).map(|(a, b)| a - b).collect();
+        if normal.iter().all(|x| *x == 0.0) {
+            return;
+        }
+        self.constraints.push(normal);
+    }
+
+    /// amount of answers the alpha contradicts
+    fn violations(&self, alpha: &[f64]) -> usize {
+        self.constraints
+            .iter()
+            .filter(|normal| {
+                let length = normal.iter().map(|x| x * x).sum::<f64>().sqrt();
+                let product: f64 = alpha.iter().zip(normal.iter()).map(|(a, n)| a * n).sum();
+                product > 1e-9 * length
+            })
+            .count()
+    }
+
+    /// samples matching all answers.
+    /// contradicting answers leave the samples with the fewest violations
+    pub fn feasible(&self) -> Vec<&Vec<f64>> {
+        let violations: Vec<usize> = self
+            .samples
+            .iter()
+            .map(|sample| self.violations(sample))
+            .collect();
+        let minimum = violations.iter().min().copied().unwrap_or(0);
+        self.samples
+            .iter()
+            .zip(violations)
+            .filter(|(_, violations)| *violations == minimum)
+            .map(|(sample, _)| sample)
+            .collect()
+    }
+
+    /// centroid of the feasible alphas, the learned preference
+    pub fn estimate(&self) -> Vec<f64> {
+        let feasible = self.feasible();
+        let mut centroid = vec![0.0; self.dim];
+        for sample in &feasible {
+            for (c, x) in centroid.iter_mut().zip(sample.iter()) {
+                *c += x;
+            }
+        }
+        centroid.iter().map(|c| c / feasible.len() as f64).collect()
+    }
+
+    /// largest distance of a feasible alpha to the estimate
+    pub fn spread(&self) -> f64 {
+        let estimate = self.estimate();
+        self.feasible()
+            .iter()
+            .map(|sample| distance(sample, &estimate))
+            .fold(0.0, f64::max)
+    }
+
+    /// pairs of far apart feasible alphas, best first.
+    /// their routes may be identical, so the caller tries them in order
+    pub fn candidate_pairs(&self, amount: usize) -> Vec<(Vec<f64>, Vec<f64>)> {
+        let feasible = self.feasible();
+        let estimate = self.estimate();
+        let mut by_distance = feasible.clone();
+        by_distance.sort_by(|a, b| {
+            distance(b, &estimate)
+                .partial_cmp(&distance(a, &estimate))
+                .unwrap()
+        });
+
+        let mut pairs: Vec<(Vec<f64>, Vec<f64>)> = Vec::with_capacity(amount);
+        for first in by_distance {
+            if pairs.len() >= amount {
+                break;
+            }
+            let second = feasible
+                .iter()
+                .max_by(|a, b| distance(a, first).partial_cmp(&distance(b, first)).unwrap())
+                .unwrap();
+            if distance(first, second) == 0.0
+                || pairs
+                    .iter()
+                    .any(|(a, b)| (a == *second && b == first) || a == first)
+            {
+                continue;
+            }
+            pairs.push((first.clone(), (*second).clone()));
+        }
+        pairs
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// a proposed route with the alpha it was computed for
+#[derive(Clone, Debug)]
+pub struct RouteOption {
+    pub alpha: Vec<f64>,
+    pub path: Vec<EdgeId>,
+    pub costs: Vec<Cost>,
+}
+
+/// elicitation of one user for one start and end
+#[derive(Clone, Debug)]
+pub struct ElicitationSession {
+    pub graph: String,
+    pub start: Location,
+    pub end: Location,
+    pub region: PreferenceRegion,
+    /// routes waiting for an answer
+    pub options: Vec<RouteOption>,
+    pub rounds: usize,
+    pub done: bool,
+}
+
+impl ElicitationSession {
+    pub fn new(graph: &str, start: Location, end: Location, dim: usize, seed: u64) -> Self {
+        ElicitationSession {
+            graph: graph.to_string(),
+            start,
+            end,
+            region: PreferenceRegion::new(dim, DEFAULT_SAMPLES, seed),
+            options: Vec::new(),
+            rounds: 0,
+            done: false,
+        }
+    }
+
+    /// enough answers or the remaining alphas are close to each other
+    pub fn is_converged(&self) -> bool {
+        self.rounds >= MAX_ROUNDS || self.region.spread() < MIN_SPREAD
+    }
+
+    /// answer of the user, index into the proposed options
+    pub fn choose(&mut self, choice: usize) -> Result<(), String> {
+        if self.done {
+            return Err("session is already finished".to_string());
+        }
+        if choice >= self.options.len() {
+            return Err(format!(
+                "choice {} is not one of the {} options",
+                choice,
+                self.options.len()
+            ));
+        }
+        let chosen = self.options[choice].costs.clone();
+        for (index, option) in self.options.iter().enumerate() {
+            if index != choice {
+                self.region.add_preference(&chosen, &option.costs);
+            }
+        }
+        self.options.clear();
+        self.rounds += 1;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Sessions {
+    by_id: HashMap<u64, ElicitationSession>,
+    /// ids from the oldest to the newest session
+    order: VecDeque<u64>,
+}
+
+/// running sessions of the webserver
+#[derive(Default)]
+pub struct ElicitationStore {
+    sessions: Mutex<Sessions>,
+}
+
+impl ElicitationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// store the session and return its id, drops the oldest session if full
+    pub fn insert(&self, session: ElicitationSession) -> u64 {
+        let mut sessions = self.sessions.lock().unwrap();
+        let mut id = random_id();
+        while id == 0 || sessions.by_id.contains_key(&id) {
+            id = random_id();
+        }
+        sessions.by_id.insert(id, session);
+        sessions.order.push_back(id);
+        while sessions.by_id.len() > MAX_SESSIONS {
+            let oldest = sessions.order.pop_front().unwrap();
+            sessions.by_id.remove(&oldest);
+        }
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<ElicitationSession> {
+        self.sessions.lock().unwrap().by_id.get(&id).cloned()
+    }
+
+    /// modify the session while holding the lock, so concurrent answers can not overwrite each other
+    pub fn with_session_mut<T, F>(&self, id: u64, modify: F) -> Option<T>
+    where
+        F: FnOnce(&mut ElicitationSession) -> T,
+    {
+        self.sessions.lock().unwrap().by_id.get_mut(&id).map(modify)
+    }
+}
+
+/// unguessable session id, small enough to be exact as a javascript number
+fn random_id() -> u64 {
+    RandomState::new().build_hasher().finish() >> 11
+}
+
+#[test]
+fn region_learns_preference() {
+    // two metrics, the user secretly uses alpha = (0.8, 0.2)
+    let hidden = [0.8, 0.2];
+    let mut region = PreferenceRegion::new(2, 500, 42);
+    let routes = [
+        vec![10.0, 1.0],
+        vec![1.0, 10.0],
+        vec![4.0, 4.0],
+        vec![3.0, 6.0],
+        vec![2.0, 8.5],
+    ];
+    let cost = |route: &[f64]| route.iter().zip(&hidden).map(|(c, a)| c * a).sum::<f64>();
+    for a in &routes {
+        for b in &routes {
+            if cost(a) < cost(b) {
+                region.add_preference(a, b);
+            }
+        }
+    }
+    let estimate = region.estimate();
+    assert!((estimate.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(estimate[0] > 0.6, "estimate {:?}", estimate);
+    for alpha in region.feasible() {
+        assert_eq!(region.violations(alpha), 0);
+    }
+}
+
+#[test]
+fn candidate_pairs_are_far_apart() {
+    let region = PreferenceRegion::new(3, 200, 7);
+    let pairs = region.candidate_pairs(3);
+    assert_eq!(pairs.len(), 3);
+    assert!(distance(&pairs[0].0, &pairs[0].1) > 1.0);
+    // same seed, same proposals
+    let again = PreferenceRegion::new(3, 200, 7);
+    assert_eq!(again.candidate_pairs(3), pairs);
+}
+
+#[test]
+fn session_choice() {
+    let location = Location {
+        latitude: 0.0,
+        longitude: 0.0,
+    };
+    let mut session = ElicitationSession::new("default", location.clone(), location, 2, 1);
+    assert!(session.choose(0).is_err());
+    session.options = vec![
+        RouteOption {
+            alpha: vec![1.0, 0.0],
+            path: vec![0],
+            costs: vec![1.0, 5.0],
+        },
+        RouteOption {
+            alpha: vec![0.0, 1.0],
+            path: vec![1],
+            costs: vec![5.0, 1.0],
+        },
+    ];
+    session.choose(1).unwrap();
+    assert_eq!(session.rounds, 1);
+    assert_eq!(session.region.amount_constraints(), 1);
+    assert!(session.region.estimate()[1] > 0.5);
+}
+
+#[test]
+fn store_sessions() {
+    let location = Location {
+        latitude: 0.0,
+        longitude: 0.0,
+    };
+    let session = ElicitationSession::new("default", location.clone(), location, 2, 1);
+    let store = ElicitationStore::new();
+    let first = store.insert(session.clone());
+    let second = store.insert(session.clone());
+    assert_ne!(first, second);
+    assert!(first < 1 << 53 && second < 1 << 53);
+
+    assert_eq!(
+        store.with_session_mut(first, |session| session.rounds += 1),
+        Some(())
+    );
+    assert_eq!(store.get(first).unwrap().rounds, 1);
+    assert_eq!(store.get(second).unwrap().rounds, 0);
+    assert!(store.with_session_mut(0, |_| ()).is_none());
+
+    // the oldest sessions get dropped first
+    for _ in 0..MAX_SESSIONS - 1 {
+        store.insert(session.clone());
+    }
+    assert!(store.get(first).is_none());
+    assert!(store.get(second).is_some());
+}
@@ -1,9 +1,13 @@
+use crate::alpha::Alpha;
 use crate::constants::*;
+use crate::structs::{GridBounds, QueryType};
+use crate::tradeoff::Tradeoff;
 
 use actix_web::http::StatusCode;
 use actix_web::{HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, to_string_pretty};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 // r#type for escaping the rust-type command to normal type string
@@ -17,7 +21,25 @@ pub struct Point {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Property {
     pub cost: Option<String>,
-    pub alpha: Option<Vec<f64>>,
+    /// list of weights or map from metric name to weight
+    pub alpha: Option<Alpha>,
+    /// scale alpha onto the simplex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<bool>,
+    /// stored profile used instead of alpha
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<u64>,
+    /// algorithm used instead of the query type of the graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_type: Option<QueryType>,
+    /// request the comparison against the single-metric optimal routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tradeoffs: Option<Vec<Tradeoff>>,
+    /// meters between the clicked points and the snapped start and end
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snap_distance: Option<Vec<f64>>,
 }
 
 // request are two points
@@ -60,6 +82,114 @@ pub struct GeoJsonResponse {
     pub features: Vec<FeatureResponse>,
 }
 
+// search space of a single query, split by search direction
+#[derive(Serialize, Debug)]
+pub struct SearchSpaceProperty {
+    pub level: Option<Level>,
+    pub color: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchSpaceFeature {
+    pub r#type: String,
+    pub properties: SearchSpaceProperty,
+    pub geometry: GeometryResponse,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchSpaceLayer {
+    pub r#type: String,
+    pub features: Vec<SearchSpaceFeature>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchSpaceResponse {
+    pub forward: SearchSpaceLayer,
+    pub backward: SearchSpaceLayer,
+    pub meeting_node: Option<(Angle, Angle)>,
+    pub cost: Option<String>,
+    pub heap_pops: usize,
+    pub relaxed_edges: usize,
+    pub visited_nodes: usize,
+}
+
+// every algorithm run on the same request
+#[derive(Serialize, Debug)]
+pub struct AlgorithmResult {
+    pub query_type: QueryType,
+    pub cost: Option<Cost>,
+    /// microseconds of the search
+    pub time: u128,
+    pub heap_pops: usize,
+    pub relaxed_edges: usize,
+    /// cost or existence of a path differs from the normal dijkstra
+    pub disagrees: bool,
+    /// reason, if the query was aborted
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AlgorithmsResponse {
+    pub start_id: NodeId,
+    pub end_id: NodeId,
+    pub results: Vec<AlgorithmResult>,
+    pub costs_agree: bool,
+}
+
+// elicitation proposes routes and learns alpha from the choices
+#[derive(Serialize, Debug)]
+pub struct ElicitationOption {
+    pub alpha: Vec<f64>,
+    pub costs: BTreeMap<String, Cost>,
+    pub coordinates: Vec<(Angle, Angle)>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ElicitationResponse {
+    pub session_id: u64,
+    pub round: usize,
+    pub done: bool,
+    pub options: Vec<ElicitationOption>,
+    /// current estimate of alpha, the learned profile once done
+    pub alpha: Vec<f64>,
+    pub profile: BTreeMap<String, f64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ElicitationChoice {
+    pub choice: usize,
+}
+
+// dataset metadata of the loaded bin-file
+#[derive(Serialize, Clone, Debug)]
+pub struct FileInfo {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct InfoResponse {
+    pub name: String,
+    pub grid_bounds: GridBounds,
+    pub amount_nodes: usize,
+    pub amount_edges: usize,
+    pub mlp_levels: Vec<usize>,
+    pub metrics: Vec<String>,
+    pub query_type: QueryType,
+    pub default_alpha: Option<Vec<f64>>,
+    pub file: FileInfo,
+}
+
+// snapped node with its distance in meters
+#[derive(Serialize, Debug)]
+pub struct NearestResponse {
+    pub id: NodeId,
+    pub latitude: Angle,
+    pub longitude: Angle,
+    pub distance_m: f64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct Error {
     pub msg: String,
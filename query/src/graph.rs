@@ -1,4 +1,6 @@
 use super::*;
+use std::ops::Range;
+use std::slice::Iter;
 
 #[derive(Clone, Debug)]
 pub struct Graph {
@@ -8,6 +10,8 @@ pub struct Graph {
     pub down_offset: Vec<EdgeId>,
     pub down_index: Vec<EdgeId>,
     pub dim: usize,
+    /// adjacency orders of the query types, the stored order is used for all without them
+    pub edge_orders: Option<EdgeOrders>,
 }
 
 impl Graph {
@@ -26,9 +30,15 @@ impl Graph {
             down_offset,
             down_index,
             dim,
+            edge_orders: None,
         }
     }
 
+    pub fn with_edge_orders(mut self, edge_orders: EdgeOrders) -> Self {
+        self.edge_orders = Some(edge_orders);
+        self
+    }
+
     /// get single edge
     #[allow(dead_code)]
     pub fn get_edge(&self, id: EdgeId) -> Edge {
@@ -42,57 +52,99 @@ impl Graph {
         &self.edge_costs[offset..offset + self.dim]
     }
 
+    /// sum of each metric along a path of edge-ids
+    pub fn get_path_costs(&self, path: &[EdgeId]) -> Vec<Cost> {
+        let mut costs = vec![0.0; self.dim];
+        for edge_id in path {
+            for (cost, edge_cost) in costs.iter_mut().zip(self.get_edge_costs(*edge_id)) {
+                *cost += edge_cost;
+            }
+        }
+        costs
+    }
+
+    /// nodes along a path of edge-ids
+    pub fn get_path_node_ids(&self, path: &[EdgeId]) -> Vec<NodeId> {
+        if path.is_empty() {
+            return vec![];
+        }
+        let mut nodes: Vec<NodeId> = path
+            .iter()
+            .map(|edge_id| self.edges[*edge_id].from)
+            .collect();
+        nodes.push(self.edges[*path.last().unwrap()].to);
+        nodes
+    }
+
     /// get all up edges from one node
     #[allow(dead_code)]
-    pub fn get_edges_from_id(&self, ids: Vec<EdgeId>) -> Vec<Edge> {
+    pub fn get_edges_from_id(&self, ids: &[EdgeId]) -> Vec<Edge> {
         ids.iter().map(|x| self.edges[*x]).collect()
     }
 
     /// get all up edge-ids from one node
-    #[allow(dead_code)]
-    pub fn get_up_edge_ids(&self, node: NodeId) -> Vec<EdgeId> {
-        (self.up_offset[node]..self.up_offset[node + 1]).collect()
+    pub fn get_up_edge_ids(&self, node: NodeId) -> EdgeIds<'_> {
+        EdgeIds::Range(self.up_offset[node]..self.up_offset[node + 1])
     }
 
     /// get all down edge-ids from one node
-    #[allow(dead_code)]
-    pub fn get_down_edge_ids(&self, node: NodeId) -> Vec<EdgeId> {
-        let prev: Vec<EdgeId> = (self.down_offset[node]..self.down_offset[node + 1]).collect();
-        prev.iter().map(|x| self.down_index[*x]).collect()
+    pub fn get_down_edge_ids(&self, node: NodeId) -> EdgeIds<'_> {
+        EdgeIds::Indexed(self.down_index[self.down_offset[node]..self.down_offset[node + 1]].iter())
+    }
+
+    /// get all up edge-ids from one node in the order of a query type
+    pub fn get_ordered_up_edge_ids(&self, query_type: QueryType, node: NodeId) -> EdgeIds<'_> {
+        match &self.edge_orders {
+            Some(orders) => EdgeIds::Indexed(
+                orders.get(query_type).up[self.up_offset[node]..self.up_offset[node + 1]].iter(),
+            ),
+            None => self.get_up_edge_ids(node),
+        }
+    }
+
+    /// get all down edge-ids from one node in the order of a query type
+    pub fn get_ordered_down_edge_ids(&self, query_type: QueryType, node: NodeId) -> EdgeIds<'_> {
+        match &self.edge_orders {
+            Some(orders) => EdgeIds::Indexed(
+                orders.get(query_type).down[self.down_offset[node]..self.down_offset[node + 1]]
+                    .iter(),
+            ),
+            None => self.get_down_edge_ids(node),
+        }
     }
 
     /// get all edge-ids from one node
     #[allow(dead_code)]
-    pub fn get_edge_ids(&self, node: NodeId) -> (Vec<EdgeId>, Vec<EdgeId>) {
-        let outgoing: Vec<NodeId> = self.get_up_edge_ids(node);
-        let incomming: Vec<NodeId> = self.get_down_edge_ids(node);
-        (outgoing, incomming)
+    pub fn get_edge_ids(&self, node: NodeId) -> (EdgeIds<'_>, EdgeIds<'_>) {
+        (self.get_up_edge_ids(node), self.get_down_edge_ids(node))
     }
 
     /// get all edge-ids from one node
-    #[allow(dead_code)]
-    pub fn get_all_edge_ids(&self, node: NodeId) -> Vec<EdgeId> {
+    pub fn get_all_edge_ids(&self, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
         let (outgoing, incomming) = self.get_edge_ids(node);
-        let mut connected_edges = outgoing;
-        connected_edges.extend(&incomming);
-        connected_edges
+        outgoing.chain(incomming)
     }
 
     /// get all up neighbors from one node
     #[allow(dead_code)]
-    pub fn get_up_neighbors(&self, node: NodeId) -> Vec<EdgeId> {
-        let next = self.get_up_edge_ids(node);
-        let mut tmp: Vec<EdgeId> = next.iter().map(|x| self.edges[*x].to).collect();
+    pub fn get_up_neighbors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut tmp: Vec<NodeId> = self
+            .get_up_edge_ids(node)
+            .map(|x| self.edges[x].to)
+            .collect();
         tmp.dedup();
         tmp
     }
 
     /// get all up neighbors from one node
     #[allow(dead_code)]
-    pub fn get_down_neighbors(&self, node: NodeId) -> Vec<EdgeId> {
-        let prev = self.get_down_edge_ids(node);
-        let mut tmp: Vec<EdgeId> = prev.iter().map(|x| self.edges[*x].from).collect();
-        tmp.par_sort_unstable();
+    pub fn get_down_neighbors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut tmp: Vec<NodeId> = self
+            .get_down_edge_ids(node)
+            .map(|x| self.edges[x].from)
+            .collect();
+        // only a handful of neighbors, sorting in parallel does not pay off
+        tmp.sort_unstable();
         tmp.dedup();
         tmp
     }
@@ -111,7 +163,7 @@ impl Graph {
         let (targets, sources) = self.get_neighbours(node);
         let mut neighbours = targets;
         neighbours.extend(&sources);
-        neighbours.par_sort_unstable();
+        neighbours.sort_unstable();
         neighbours.dedup();
         neighbours
     }
@@ -123,3 +175,93 @@ impl Graph {
             - self.down_offset[node]
     }
 }
+
+/// edge-ids of one node, borrowed from the offset arrays instead of collected
+#[derive(Clone, Debug)]
+pub enum EdgeIds<'a> {
+    /// up edges are stored consecutively
+    Range(Range<EdgeId>),
+    /// down edges and ordered edges are referenced through an index
+    Indexed(Iter<'a, EdgeId>),
+}
+
+impl<'a> Iterator for EdgeIds<'a> {
+    type Item = EdgeId;
+
+    #[inline]
+    fn next(&mut self) -> Option<EdgeId> {
+        match self {
+            EdgeIds::Range(range) => range.next(),
+            EdgeIds::Indexed(iter) => iter.next().copied(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            EdgeIds::Range(range) => range.size_hint(),
+            EdgeIds::Indexed(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for EdgeIds<'a> {}
+
+#[test]
+fn adjacency() {
+    // 0 -> 1, 0 -> 2, 1 -> 2, edges sorted by source
+    let edge = |from, to| Edge {
+        from,
+        to,
+        level: None,
+        contracted_edges: None,
+        old_id: None,
+    };
+    let graph = Graph::new(
+        vec![edge(0, 1), edge(0, 2), edge(1, 2)],
+        vec![1.0, 1.0, 1.0],
+        vec![0, 2, 3, 3],
+        vec![0, 0, 1, 3],
+        vec![0, 1, 2],
+        1,
+    );
+    assert_eq!(graph.get_up_edge_ids(0).collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!(graph.get_up_edge_ids(0).len(), 2);
+    assert_eq!(graph.get_down_edge_ids(2).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(graph.get_all_edge_ids(1).collect::<Vec<_>>(), vec![2, 0]);
+    assert_eq!(graph.get_down_neighbors(2), vec![0, 1]);
+    assert_eq!(graph.get_all_neighbours(1), vec![0, 2]);
+
+    // without orders every query type uses the stored order
+    assert_eq!(
+        graph
+            .get_ordered_up_edge_ids(QueryType::Pch, 0)
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    let order = |up: Vec<EdgeId>, down: Vec<EdgeId>| EdgeOrder { up, down };
+    let graph = graph.with_edge_orders(EdgeOrders {
+        normal: order(vec![0, 1, 2], vec![0, 1, 2]),
+        pch: order(vec![1, 0, 2], vec![0, 2, 1]),
+        pcrp: order(vec![0, 1, 2], vec![0, 1, 2]),
+        prp: order(vec![0, 1, 2], vec![0, 1, 2]),
+    });
+    assert_eq!(
+        graph
+            .get_ordered_up_edge_ids(QueryType::Pch, 0)
+            .collect::<Vec<_>>(),
+        vec![1, 0]
+    );
+    assert_eq!(
+        graph
+            .get_ordered_down_edge_ids(QueryType::Pch, 2)
+            .collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+    assert_eq!(
+        graph
+            .get_ordered_up_edge_ids(QueryType::Bi, 0)
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+}
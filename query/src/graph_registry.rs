@@ -0,0 +1,342 @@
+use super::*;
+use crate::geojson::{FileInfo, InfoResponse};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// one graph served under its own name (e.g. car or bicycle)
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GraphConfig {
+    pub name: String,
+    pub file: String,
+    pub query_type: QueryType,
+    #[serde(default)]
+    pub default_alpha: Option<Alpha>,
+    /// queries running longer get aborted
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub max_settled_nodes: Option<usize>,
+    /// snap to the largest strongly connected component instead of islands
+    #[serde(default)]
+    pub snap_to_main_component: bool,
+    /// clicked points further away from every node in meters are rejected
+    #[serde(default)]
+    pub max_snap_distance: Option<f64>,
+}
+
+/// used if the config does not set a timeout
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_TIMEOUT_MS
+}
+
+impl GraphConfig {
+    /// limits of a query starting now
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            max_settled_nodes: self.max_settled_nodes,
+            deadline: Some(Instant::now() + Duration::from_millis(self.timeout_ms)),
+            ..QueryLimits::default()
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ServerConfig {
+    pub graphs: Vec<GraphConfig>,
+}
+
+impl ServerConfig {
+    /// read json-config listing all graphs, the first one is the default graph
+    pub fn read_file(file_path: &str) -> Result<ServerConfig, Box<dyn std::error::Error>> {
+        let file = File::open(file_path)?;
+        let config: ServerConfig = serde_json::from_reader(BufReader::new(file))?;
+        if config.graphs.is_empty() {
+            return Err("config does not contain any graph".into());
+        }
+        let names: BTreeSet<&str> = config.graphs.iter().map(|g| g.name.as_str()).collect();
+        if names.len() != config.graphs.len() {
+            return Err("graph names are not unique".into());
+        }
+        Ok(config)
+    }
+}
+
+pub struct LoadedGraph {
+    pub config: GraphConfig,
+    /// shared by all graphs loaded from the same bin-file
+    pub data: Arc<WebData>,
+    pub file: FileInfo,
+    pub modified: Option<SystemTime>,
+    /// increases with every (re)load of any graph
+    pub generation: usize,
+    /// cancelled when the server shuts down
+    pub shutdown: CancelToken,
+}
+
+impl LoadedGraph {
+    /// limits of a query starting now, which is also cancelled by the shutdown
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            cancel: Some(self.shutdown.clone()),
+            ..self.config.query_limits()
+        }
+    }
+
+    /// default alpha in the metric order of the graph, validated while loading
+    pub fn default_alpha(&self) -> Option<Vec<f64>> {
+        self.config
+            .default_alpha
+            .as_ref()
+            .and_then(|alpha| alpha.resolve(&self.data.metrics).ok())
+    }
+
+    pub fn info(&self) -> InfoResponse {
+        InfoResponse {
+            name: self.config.name.clone(),
+            grid_bounds: self.data.grid_bounds.clone(),
+            amount_nodes: self.data.nodes.len(),
+            amount_edges: self.data.graph.edges.len(),
+            mlp_levels: self.data.mlp_levels.clone(),
+            metrics: self.data.metrics.clone(),
+            query_type: self.config.query_type,
+            default_alpha: self.default_alpha(),
+            file: self.file.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadState {
+    Loading,
+    Ready,
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GraphStatus {
+    pub name: String,
+    pub state: LoadState,
+    pub reloading: bool,
+    pub generation: Option<usize>,
+    pub file: Option<FileInfo>,
+    pub error: Option<String>,
+}
+
+struct GraphSlot {
+    config: GraphConfig,
+    current: RwLock<Option<Arc<LoadedGraph>>>,
+    last_error: RwLock<Option<String>>,
+    reloading: Mutex<()>,
+}
+
+/// holds all served graphs and swaps them atomically on reload.
+/// running queries keep their `Arc` of the old graph until they are done.
+pub struct GraphRegistry {
+    slots: Vec<GraphSlot>,
+    generation: AtomicUsize,
+    shutdown: CancelToken,
+}
+
+impl GraphRegistry {
+    pub fn new(configs: Vec<GraphConfig>) -> Self {
+        GraphRegistry {
+            slots: configs
+                .into_iter()
+                .map(|config| GraphSlot {
+                    config,
+                    current: RwLock::new(None),
+                    last_error: RwLock::new(None),
+                    reloading: Mutex::new(()),
+                })
+                .collect(),
+            generation: AtomicUsize::new(0),
+            shutdown: CancelToken::new(),
+        }
+    }
+
+    /// abort all running and following queries of every graph
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// names of all configured graphs
+    pub fn names(&self) -> Vec<String> {
+        self.slots
+            .iter()
+            .map(|slot| slot.config.name.clone())
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.slot(name).is_some()
+    }
+
+    /// get currently loaded version of a graph
+    pub fn get(&self, name: &str) -> Option<Arc<LoadedGraph>> {
+        self.slot(name)
+            .and_then(|slot| slot.current.read().unwrap().clone())
+    }
+
+    /// the first configured graph
+    pub fn default_graph(&self) -> Option<Arc<LoadedGraph>> {
+        self.slots
+            .first()
+            .and_then(|slot| slot.current.read().unwrap().clone())
+    }
+
+    /// every graph has been loaded at least once
+    pub fn is_ready(&self) -> bool {
+        self.slots
+            .iter()
+            .all(|slot| slot.current.read().unwrap().is_some())
+    }
+
+    pub fn status(&self) -> Vec<GraphStatus> {
+        self.slots
+            .iter()
+            .map(|slot| {
+                let current = slot.current.read().unwrap().clone();
+                let error = slot.last_error.read().unwrap().clone();
+                let reloading = slot.reloading.try_lock().is_err();
+                let state = match (&current, &error) {
+                    (Some(_), _) => LoadState::Ready,
+                    (None, Some(_)) if !reloading => LoadState::Failed,
+                    (None, _) => LoadState::Loading,
+                };
+                GraphStatus {
+                    name: slot.config.name.clone(),
+                    state,
+                    reloading,
+                    generation: current.as_ref().map(|graph| graph.generation),
+                    file: current.as_ref().map(|graph| graph.file.clone()),
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// load all graphs one after another
+    pub fn load_all(&self) -> Vec<(String, Result<(), String>)> {
+        self.slots
+            .iter()
+            .map(|slot| (slot.config.name.clone(), self.load_slot(slot)))
+            .collect()
+    }
+
+    /// reload a single graph, the old one is served until the new one is ready
+    pub fn reload(&self, name: &str) -> Result<(), String> {
+        match self.slot(name) {
+            Some(slot) => self.load_slot(slot),
+            None => Err(format!("unknown graph '{}'", name)),
+        }
+    }
+
+    /// reload all graphs, whose bin-file changed on disk
+    pub fn reload_changed(&self) -> Vec<(String, Result<(), String>)> {
+        let mut results = Vec::new();
+        for slot in &self.slots {
+            // skip graphs, that are currently loading
+            if slot.reloading.try_lock().is_err() {
+                continue;
+            }
+            let metadata = match std::fs::metadata(&slot.config.file) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let current = slot.current.read().unwrap().clone();
+            let changed = match current {
+                Some(graph) => {
+                    graph.modified != metadata.modified().ok() || graph.file.size != metadata.len()
+                }
+                None => false,
+            };
+            if changed {
+                results.push((slot.config.name.clone(), self.load_slot(slot)));
+            }
+        }
+        results
+    }
+
+    /// currently loaded graphs, whose data can be reused for other query types
+    fn loaded_data(&self) -> Vec<Arc<LoadedGraph>> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.current.read().unwrap().clone())
+            .collect()
+    }
+
+    fn slot(&self, name: &str) -> Option<&GraphSlot> {
+        self.slots.iter().find(|slot| slot.config.name == name)
+    }
+
+    fn load_slot(&self, slot: &GraphSlot) -> Result<(), String> {
+        let _guard = slot.reloading.lock().unwrap();
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst);
+        match load_graph(
+            &slot.config,
+            generation,
+            &self.shutdown,
+            &self.loaded_data(),
+        ) {
+            Ok(graph) => {
+                *slot.current.write().unwrap() = Some(Arc::new(graph));
+                *slot.last_error.write().unwrap() = None;
+                Ok(())
+            }
+            Err(error) => {
+                let msg = format!("error while loading '{}': {}", slot.config.file, error);
+                *slot.last_error.write().unwrap() = Some(msg.clone());
+                Err(msg)
+            }
+        }
+    }
+}
+
+fn load_graph(
+    config: &GraphConfig,
+    generation: usize,
+    shutdown: &CancelToken,
+    loaded: &[Arc<LoadedGraph>],
+) -> Result<LoadedGraph, Box<dyn std::error::Error>> {
+    // metadata before reading, so changes while reading trigger another reload
+    let metadata = std::fs::metadata(&config.file)?;
+    let modified = metadata.modified().ok();
+    // the bin-file contains the edge orders of all query types, so unchanged files are shared
+    let shared = loaded.iter().find(|graph| {
+        graph.file.path == config.file
+            && graph.modified == modified
+            && graph.file.size == metadata.len()
+    });
+    let data = match shared {
+        Some(graph) => graph.data.clone(),
+        None => Arc::new(bin_import::load_web_data(&config.file)?),
+    };
+    if let Some(alpha) = &config.default_alpha {
+        alpha
+            .resolve(&data.metrics)
+            .map_err(|error| format!("invalid default alpha: {}", error))?;
+    }
+    Ok(LoadedGraph {
+        config: config.clone(),
+        data,
+        file: FileInfo {
+            path: config.file.clone(),
+            size: metadata.len(),
+            modified: modified
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs()),
+        },
+        modified,
+        generation,
+        shutdown: shutdown.clone(),
+    })
+}
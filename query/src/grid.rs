@@ -24,19 +24,98 @@ pub fn get_closest_point(
     grid_offset: &[GridId],
     grid_bounds: &GridBounds,
 ) -> usize {
-    let mut minimum = Angle::MAX;
+    get_closest_point_and_distance(node, nodes, grid, grid_offset, grid_bounds).0
+}
+
+/// get node-id using grid and its distance in meters
+pub fn get_closest_point_and_distance(
+    node: Location,
+    nodes: &[Node],
+    grid: &[NodeId],
+    grid_offset: &[GridId],
+    grid_bounds: &GridBounds,
+) -> (NodeId, f64) {
+    get_closest_accepted_point(node, nodes, grid, grid_offset, grid_bounds, &|_| true)
+}
+
+/// get the closest node-id accepted by a filter and its distance in meters.
+/// INVALID_NODE if no node is accepted
+pub fn get_closest_accepted_point(
+    node: Location,
+    nodes: &[Node],
+    grid: &[NodeId],
+    grid_offset: &[GridId],
+    grid_bounds: &GridBounds,
+    accept: &dyn Fn(NodeId) -> bool,
+) -> (NodeId, f64) {
+    let mut minimum = f64::MAX;
     let mut closeset = INVALID_NODE;
 
-    let adjacent_nodes = get_adjacent_nodes(&node, grid, grid_offset, grid_bounds);
+    let adjacent_nodes = get_adjacent_nodes(&node, grid, grid_offset, grid_bounds, accept);
     for node_id in adjacent_nodes {
-        let tmp_node: Node = nodes[node_id].clone();
-        let dist = calc_distance(&node, &tmp_node);
+        let dist = calc_distance(&node, &nodes[node_id]);
         if dist < minimum {
             minimum = dist;
             closeset = node_id;
         }
     }
-    closeset
+    (closeset, minimum * 1000.0)
+}
+
+/// the k closest nodes accepted by a filter and their distances in meters, closest first.
+/// nodes further away than `max_distance` meters are skipped
+#[allow(clippy::too_many_arguments)]
+pub fn get_closest_points(
+    node: Location,
+    k: usize,
+    max_distance: Option<f64>,
+    nodes: &[Node],
+    grid: &[NodeId],
+    grid_offset: &[GridId],
+    grid_bounds: &GridBounds,
+    accept: &dyn Fn(NodeId) -> bool,
+) -> Vec<(NodeId, f64)> {
+    let grid_id_lat: isize = get_grid_lat(&node, grid_bounds) as isize;
+    let grid_id_lng: isize = get_grid_lng(&node, grid_bounds) as isize;
+    let max_grid_dist =
+        grid_id_lat.max(grid_id_lng) + grid_bounds.lat_amount.max(grid_bounds.lng_amount) as isize;
+    let max_distance = max_distance.unwrap_or(f64::MAX);
+    let cell_size = get_min_cell_size(grid_bounds);
+
+    let mut candidates = Vec::<(NodeId, f64)>::new();
+    let mut grid_dist: isize = 1;
+    // rings are squares, so the next ring may still contain closer nodes than the corners
+    let mut last_ring = false;
+    while grid_dist <= max_grid_dist {
+        let cell_ids = get_ring_cells(grid_id_lat, grid_id_lng, grid_dist, grid_bounds);
+        for node_id in get_points_from_cells(&cell_ids, grid, grid_offset) {
+            let distance = calc_distance(&node, &nodes[node_id]) * 1000.0;
+            if distance <= max_distance && accept(node_id) {
+                candidates.push((node_id, distance));
+            }
+        }
+        if last_ring || (grid_dist - 1) as f64 * cell_size > max_distance {
+            break;
+        }
+        last_ring = candidates.len() >= k;
+        grid_dist += 1;
+    }
+    candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    candidates.truncate(k);
+    candidates
+}
+
+/// smallest side of a grid cell in meters
+fn get_min_cell_size(grid_bounds: &GridBounds) -> f64 {
+    // meters of one degree latitude
+    let degree = 6371.0 * 1000.0 * std::f64::consts::PI / 180.0;
+    let lat_size =
+        (grid_bounds.lat_max - grid_bounds.lat_min) / (grid_bounds.lat_amount.max(2) - 1) as Angle;
+    let lng_size =
+        (grid_bounds.lng_max - grid_bounds.lng_min) / (grid_bounds.lng_amount.max(2) - 1) as Angle;
+    // longitudes are closest at the latitude furthest from the equator
+    let max_lat = grid_bounds.lat_min.abs().max(grid_bounds.lat_max.abs());
+    (lat_size * degree).min(lng_size * degree * max_lat.to_radians().cos())
 }
 
 /// get close node_ids
@@ -45,86 +124,29 @@ fn get_adjacent_nodes(
     grid: &[NodeId],
     grid_offset: &[GridId],
     grid_bounds: &GridBounds,
+    accept: &dyn Fn(NodeId) -> bool,
 ) -> Vec<NodeId> {
     let grid_id_lat: isize = get_grid_lat(node, grid_bounds) as isize;
     let grid_id_lng: isize = get_grid_lng(node, grid_bounds) as isize;
     // println!("grid_id_lat {:?}", grid_id_lat);
     // println!("grid_id_lng {:?}", grid_id_lng);
     let mut grid_dist: isize = 1;
+    // a ring this large contains the whole grid
+    let max_grid_dist =
+        grid_id_lat.max(grid_id_lng) + grid_bounds.lat_amount.max(grid_bounds.lng_amount) as isize;
 
     loop {
-        let mut cell_ids = Vec::<GridId>::new();
-        // moving in circle around the target
-        for i in -grid_dist..(grid_dist) {
-            // first iteration add the middle
-            if grid_dist == 1
-                && i == 0
-                && grid_id_lat >= 0
-                && grid_id_lng >= 0
-                && grid_id_lat < (grid_bounds.lat_amount as isize)
-                && grid_id_lng < (grid_bounds.lng_amount as isize)
-            {
-                cell_ids.push(calculate_grid_id(
-                    (grid_id_lat) as usize,
-                    (grid_id_lng) as usize,
-                    grid_bounds,
-                ));
-            }
-            // north left to right
-            if grid_id_lat + i >= 0
-                && grid_id_lng + grid_dist >= 0
-                && grid_id_lat + i < (grid_bounds.lat_amount as isize)
-                && grid_id_lng + grid_dist < (grid_bounds.lng_amount as isize)
-            {
-                cell_ids.push(calculate_grid_id(
-                    (grid_id_lat + i) as usize,
-                    (grid_id_lng + grid_dist) as usize,
-                    grid_bounds,
-                ));
-            }
-            // east top to bottom
-            if grid_id_lat + grid_dist >= 0
-                && grid_id_lng - i >= 0
-                && grid_id_lat + grid_dist < (grid_bounds.lat_amount as isize)
-                && grid_id_lng - i < (grid_bounds.lng_amount as isize)
-            {
-                cell_ids.push(calculate_grid_id(
-                    (grid_id_lat + grid_dist) as usize,
-                    (grid_id_lng - i) as usize,
-                    grid_bounds,
-                ));
-            }
-            // south top to bottom
-            if grid_id_lat - i >= 0
-                && grid_id_lng - grid_dist >= 0
-                && grid_id_lat - i < (grid_bounds.lat_amount as isize)
-                && grid_id_lng - grid_dist < (grid_bounds.lng_amount as isize)
-            {
-                cell_ids.push(calculate_grid_id(
-                    (grid_id_lat - i) as usize,
-                    (grid_id_lng - grid_dist) as usize,
-                    grid_bounds,
-                ));
-            }
-            // west top to bottom
-            if grid_id_lat - grid_dist >= 0
-                && grid_id_lng + i >= 0
-                && grid_id_lat - grid_dist < (grid_bounds.lat_amount as isize)
-                && grid_id_lng + i < (grid_bounds.lng_amount as isize)
-            {
-                cell_ids.push(calculate_grid_id(
-                    (grid_id_lat - grid_dist) as usize,
-                    (grid_id_lng + i) as usize,
-                    grid_bounds,
-                ));
-            }
-        }
+        let cell_ids = get_ring_cells(grid_id_lat, grid_id_lng, grid_dist, grid_bounds);
 
         // get all points from cells
-        let adjacent_nodes = get_points_from_cells(&cell_ids, grid, grid_offset);
+        let mut adjacent_nodes = get_points_from_cells(&cell_ids, grid, grid_offset);
+        adjacent_nodes.retain(|node_id| accept(*node_id));
 
         if !adjacent_nodes.is_empty() {
             return adjacent_nodes;
+        } else if grid_dist > max_grid_dist {
+            // every cell has been searched
+            return adjacent_nodes;
         } else {
             // search in outer cells
             grid_dist += 1;
@@ -132,6 +154,122 @@ fn get_adjacent_nodes(
     }
 }
 
+/// cells of the square ring around a cell, the first ring contains the cell itself
+fn get_ring_cells(
+    grid_id_lat: isize,
+    grid_id_lng: isize,
+    grid_dist: isize,
+    grid_bounds: &GridBounds,
+) -> Vec<GridId> {
+    let mut cell_ids = Vec::<GridId>::new();
+    // moving in circle around the target
+    for i in -grid_dist..(grid_dist) {
+        // first iteration add the middle
+        if grid_dist == 1
+            && i == 0
+            && grid_id_lat >= 0
+            && grid_id_lng >= 0
+            && grid_id_lat < (grid_bounds.lat_amount as isize)
+            && grid_id_lng < (grid_bounds.lng_amount as isize)
+        {
+            cell_ids.push(calculate_grid_id(
+                (grid_id_lat) as usize,
+                (grid_id_lng) as usize,
+                grid_bounds,
+            ));
+        }
+        // north left to right
+        if grid_id_lat + i >= 0
+            && grid_id_lng + grid_dist >= 0
+            && grid_id_lat + i < (grid_bounds.lat_amount as isize)
+            && grid_id_lng + grid_dist < (grid_bounds.lng_amount as isize)
+        {
+            cell_ids.push(calculate_grid_id(
+                (grid_id_lat + i) as usize,
+                (grid_id_lng + grid_dist) as usize,
+                grid_bounds,
+            ));
+        }
+        // east top to bottom
+        if grid_id_lat + grid_dist >= 0
+            && grid_id_lng - i >= 0
+            && grid_id_lat + grid_dist < (grid_bounds.lat_amount as isize)
+            && grid_id_lng - i < (grid_bounds.lng_amount as isize)
+        {
+            cell_ids.push(calculate_grid_id(
+                (grid_id_lat + grid_dist) as usize,
+                (grid_id_lng - i) as usize,
+                grid_bounds,
+            ));
+        }
+        // south top to bottom
+        if grid_id_lat - i >= 0
+            && grid_id_lng - grid_dist >= 0
+            && grid_id_lat - i < (grid_bounds.lat_amount as isize)
+            && grid_id_lng - grid_dist < (grid_bounds.lng_amount as isize)
+        {
+            cell_ids.push(calculate_grid_id(
+                (grid_id_lat - i) as usize,
+                (grid_id_lng - grid_dist) as usize,
+                grid_bounds,
+            ));
+        }
+        // west top to bottom
+        if grid_id_lat - grid_dist >= 0
+            && grid_id_lng + i >= 0
+            && grid_id_lat - grid_dist < (grid_bounds.lat_amount as isize)
+            && grid_id_lng + i < (grid_bounds.lng_amount as isize)
+        {
+            cell_ids.push(calculate_grid_id(
+                (grid_id_lat - grid_dist) as usize,
+                (grid_id_lng + i) as usize,
+                grid_bounds,
+            ));
+        }
+    }
+    cell_ids
+}
+
+/// get node-ids inside a bounding box, e.g. a map tile
+pub fn get_points_in_bounds(
+    min: &Location,
+    max: &Location,
+    nodes: &[Node],
+    grid: &[NodeId],
+    grid_offset: &[GridId],
+    grid_bounds: &GridBounds,
+) -> Vec<NodeId> {
+    if max.latitude < grid_bounds.lat_min
+        || min.latitude > grid_bounds.lat_max
+        || max.longitude < grid_bounds.lng_min
+        || min.longitude > grid_bounds.lng_max
+    {
+        return Vec::new();
+    }
+    // negative indices saturate to 0
+    let lat_start = get_grid_lat(min, grid_bounds);
+    let lat_end = get_grid_lat(max, grid_bounds).min(grid_bounds.lat_amount - 1);
+    let lng_start = get_grid_lng(min, grid_bounds);
+    let lng_end = get_grid_lng(max, grid_bounds).min(grid_bounds.lng_amount - 1);
+
+    let mut cell_ids = Vec::<GridId>::new();
+    for lng_index in lng_start..=lng_end {
+        for lat_index in lat_start..=lat_end {
+            cell_ids.push(calculate_grid_id(lat_index, lng_index, grid_bounds));
+        }
+    }
+    get_points_from_cells(&cell_ids, grid, grid_offset)
+        .into_iter()
+        .filter(|node_id| {
+            let node = &nodes[*node_id];
+            node.latitude >= min.latitude
+                && node.latitude <= max.latitude
+                && node.longitude >= min.longitude
+                && node.longitude <= max.longitude
+        })
+        .collect()
+}
+
 /// return node-ids from multiple cells
 fn get_points_from_cells(
     grid_ids: &[GridId],
@@ -196,9 +334,63 @@ fn calc_distance(a: &Location, b: &Node) -> Angle {
 }
 
 /// converts node ids to nodes
+#[allow(clippy::needless_return)]
 pub fn get_coordinates(path: Vec<NodeId>, nodes: &[Node]) -> Vec<Node> {
     return path
         .par_iter()
         .map(|x| nodes[*x].clone())
         .collect::<Vec<Node>>();
 }
+
+#[test]
+fn closest_points() {
+    let node = |latitude, longitude| Node {
+        latitude,
+        longitude,
+        rank: 0,
+        partition: 0,
+    };
+    // one node per cell of a 2x2 grid, cell ids are lng_index * lat_amount + lat_index
+    let nodes = vec![
+        node(0.0, 0.0),
+        node(0.01, 0.0),
+        node(0.0, 0.01),
+        node(0.01, 0.01),
+    ];
+    let grid = vec![0, 1, 2, 3];
+    let grid_offset = vec![0, 1, 2, 3, 4];
+    let grid_bounds = GridBounds {
+        lat_amount: 2,
+        lat_min: 0.0,
+        lat_max: 0.01,
+        lng_amount: 2,
+        lng_min: 0.0,
+        lng_max: 0.01,
+    };
+    let location = Location {
+        latitude: 0.001,
+        longitude: 0.0,
+    };
+    let closest = |k, max_distance, accept: &dyn Fn(NodeId) -> bool| {
+        get_closest_points(
+            location.clone(),
+            k,
+            max_distance,
+            &nodes,
+            &grid,
+            &grid_offset,
+            &grid_bounds,
+            accept,
+        )
+        .into_iter()
+        .map(|(node_id, _)| node_id)
+        .collect::<Vec<NodeId>>()
+    };
+
+    assert_eq!(closest(2, None, &|_| true), vec![0, 1]);
+    assert_eq!(closest(4, None, &|_| true), vec![0, 1, 2, 3]);
+    assert_eq!(closest(3, None, &|node_id| node_id != 0), vec![1, 2, 3]);
+    // node 0 is about 111 m away, node 1 about 1 km
+    assert_eq!(closest(3, Some(500.0), &|_| true), vec![0]);
+    assert!(closest(3, Some(50.0), &|_| true).is_empty());
+}
@@ -1,20 +1,29 @@
+pub mod alpha;
 pub mod bin_import;
+pub mod components;
 pub mod constants;
 pub mod dijkstra;
+pub mod elicitation;
 pub mod export;
 pub mod geojson;
 pub mod graph;
+pub mod graph_registry;
 pub mod grid;
 pub mod min_heap;
 pub mod mlp_helper;
+pub mod monitoring;
+pub mod profiles;
 pub mod query_export;
-pub mod sort_edges;
 pub mod structs;
+pub mod tradeoff;
 pub mod valid_flag;
+pub mod vector_tile;
 
+pub use alpha::Alpha;
+pub use components::Components;
 pub use constants::*;
-pub use dijkstra::FindPath;
-pub use graph::Graph;
+pub use dijkstra::{
+    AbortReason, CancelToken, FindPath, QueryLimits, QueryOptions, QueryRequest, QueryResult,
+};
+pub use graph::{EdgeIds, Graph};
 pub use structs::*;
-
-use rayon::prelude::*;
@@ -70,9 +70,8 @@ pub fn calculate_node_levels(
 ) -> usize {
     graph
         .get_all_edge_ids(node_id)
-        .iter()
         .map(|edge_id| {
-            let edge = graph.get_edge(*edge_id);
+            let edge = graph.get_edge(edge_id);
             // only calculated via edges, that existed before contraction
             if edge.contracted_edges.is_some() {
                 0
@@ -104,8 +103,7 @@ pub fn calculate_levels(nodes: &[Node], graph: &Graph, mlp_levels: &[usize]) ->
         let node_edges = graph.get_all_edge_ids(node_id);
         level_height.push(
             node_edges
-                .iter()
-                .map(|edge_id| highest_edge_diff[*edge_id])
+                .map(|edge_id| highest_edge_diff[edge_id])
                 .max()
                 .unwrap_or(0),
         );
@@ -0,0 +1,284 @@
+use super::*;
+use query_export::Counter;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// upper bounds of the latency buckets in seconds
+const LATENCY_BUCKETS: [f64; 15] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Histogram {
+            bucket_counts: vec![0; LATENCY_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.bucket_counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// write in prometheus text format with cumulative buckets
+    fn write(&self, output: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (bound, bucket_count) in LATENCY_BUCKETS.iter().zip(&self.bucket_counts) {
+            cumulative += bucket_count;
+            writeln!(
+                output,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                name, labels, bound, cumulative
+            )
+            .unwrap();
+        }
+        writeln!(
+            output,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            name, labels, self.count
+        )
+        .unwrap();
+        writeln!(output, "{}_sum{{{}}} {}", name, labels, self.sum).unwrap();
+        writeln!(output, "{}_count{{{}}} {}", name, labels, self.count).unwrap();
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct QueryMetrics {
+    snapping: Histogram,
+    search: Histogram,
+    total: Histogram,
+    queries: u64,
+    no_path: u64,
+    heap_pops: u64,
+    relaxed_edges: u64,
+}
+
+/// timings of a single query
+pub struct QueryTimings {
+    pub snapping: Duration,
+    pub search: Duration,
+    pub total: Duration,
+}
+
+/// labels are graph, query-type and the metric with the highest alpha
+type QueryLabels = (String, String, String);
+
+/// name, help-text and accessor of an exported metric
+type HistogramDescription = (&'static str, &'static str, fn(&QueryMetrics) -> &Histogram);
+type CounterDescription = (&'static str, &'static str, fn(&QueryMetrics) -> u64);
+
+/// query statistics of the webserver, shared by all workers
+#[derive(Default)]
+pub struct Monitoring {
+    queries: Mutex<BTreeMap<QueryLabels, QueryMetrics>>,
+    /// by route pattern and status
+    request_errors: Mutex<BTreeMap<(String, u16), u64>>,
+}
+
+impl Monitoring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_query(
+        &self,
+        graph: &str,
+        query_type: QueryType,
+        dominant_metric: &str,
+        timings: &QueryTimings,
+        counter: &Counter,
+        found_path: bool,
+    ) {
+        let mut queries = self.queries.lock().unwrap();
+        let metrics = queries
+            .entry((
+                graph.to_string(),
+                query_type.to_string(),
+                dominant_metric.to_string(),
+            ))
+            .or_default();
+        metrics.snapping.observe(timings.snapping.as_secs_f64());
+        metrics.search.observe(timings.search.as_secs_f64());
+        metrics.total.observe(timings.total.as_secs_f64());
+        metrics.queries += 1;
+        if !found_path {
+            metrics.no_path += 1;
+        }
+        metrics.heap_pops += counter.heap_pops as u64;
+        metrics.relaxed_edges += counter.relaxed_edges as u64;
+    }
+
+    /// failed request of an api route, e.g. `/graphs/{graph}/dijkstra`
+    pub fn record_error(&self, route: &str, status: u16) {
+        *self
+            .request_errors
+            .lock()
+            .unwrap()
+            .entry((route.to_string(), status))
+            .or_insert(0) += 1;
+    }
+
+    /// render all metrics in prometheus text format
+    pub fn render(&self) -> String {
+        let queries = self.queries.lock().unwrap();
+        let mut output = String::new();
+
+        let histograms: [HistogramDescription; 3] = [
+            (
+                "prp_query_snapping_seconds",
+                "time for snapping start and end to the graph",
+                |metrics| &metrics.snapping,
+            ),
+            (
+                "prp_query_search_seconds",
+                "time of the search itself",
+                |metrics| &metrics.search,
+            ),
+            (
+                "prp_query_total_seconds",
+                "time of the whole request",
+                |metrics| &metrics.total,
+            ),
+        ];
+        for (name, help, histogram) in histograms.iter() {
+            writeln!(output, "# HELP {} {}", name, help).unwrap();
+            writeln!(output, "# TYPE {} histogram", name).unwrap();
+            for (labels, metrics) in queries.iter() {
+                histogram(metrics).write(&mut output, name, &format_labels(labels));
+            }
+        }
+
+        let counters: [CounterDescription; 4] = [
+            (
+                "prp_queries_total",
+                "amount of answered queries",
+                |metrics| metrics.queries,
+            ),
+            (
+                "prp_query_no_path_total",
+                "amount of queries without a path",
+                |metrics| metrics.no_path,
+            ),
+            (
+                "prp_query_heap_pops_total",
+                "amount of heap pops",
+                |metrics| metrics.heap_pops,
+            ),
+            (
+                "prp_query_relaxed_edges_total",
+                "amount of relaxed edges",
+                |metrics| metrics.relaxed_edges,
+            ),
+        ];
+        for (name, help, counter) in counters.iter() {
+            writeln!(output, "# HELP {} {}", name, help).unwrap();
+            writeln!(output, "# TYPE {} counter", name).unwrap();
+            for (labels, metrics) in queries.iter() {
+                writeln!(
+                    output,
+                    "{}{{{}}} {}",
+                    name,
+                    format_labels(labels),
+                    counter(metrics)
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            output,
+            "# HELP prp_request_errors_total amount of failed requests"
+        )
+        .unwrap();
+        writeln!(output, "# TYPE prp_request_errors_total counter").unwrap();
+        for ((route, status), amount) in self.request_errors.lock().unwrap().iter() {
+            writeln!(
+                output,
+                "prp_request_errors_total{{route=\"{}\",status=\"{}\"}} {}",
+                route, status, amount
+            )
+            .unwrap();
+        }
+        output
+    }
+}
+
+fn format_labels((graph, query_type, dominant_metric): &QueryLabels) -> String {
+    format!(
+        "graph=\"{}\",query_type=\"{}\",dominant_metric=\"{}\"",
+        escape_label(graph),
+        query_type,
+        escape_label(dominant_metric)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// name of the metric with the highest weight, used to group alpha distributions
+pub fn dominant_metric(alpha: &[f64], metrics: &[String]) -> String {
+    alpha
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .and_then(|(index, _)| metrics.get(index))
+        .cloned()
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[test]
+fn histogram_buckets() {
+    let mut histogram = Histogram::new();
+    histogram.observe(0.0002);
+    histogram.observe(0.003);
+    histogram.observe(10.0);
+
+    let mut output = String::new();
+    histogram.write(&mut output, "test", "graph=\"a\"");
+
+    assert!(output.contains("test_bucket{graph=\"a\",le=\"0.0001\"} 0\n"));
+    assert!(output.contains("test_bucket{graph=\"a\",le=\"0.00025\"} 1\n"));
+    assert!(output.contains("test_bucket{graph=\"a\",le=\"0.005\"} 2\n"));
+    assert!(output.contains("test_bucket{graph=\"a\",le=\"5\"} 2\n"));
+    assert!(output.contains("test_bucket{graph=\"a\",le=\"+Inf\"} 3\n"));
+    assert!(output.contains("test_count{graph=\"a\"} 3\n"));
+}
+
+#[test]
+fn request_errors_by_route() {
+    let monitoring = Monitoring::new();
+    monitoring.record_error("/dijkstra", 400);
+    monitoring.record_error("/dijkstra", 400);
+    monitoring.record_error("/graphs/{graph}/info", 404);
+
+    let output = monitoring.render();
+    assert!(output.contains("prp_request_errors_total{route=\"/dijkstra\",status=\"400\"} 2\n"));
+    assert!(output
+        .contains("prp_request_errors_total{route=\"/graphs/{graph}/info\",status=\"404\"} 1\n"));
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+pub type ProfileId = u64;
+
+/// named alpha of a user, weights are keyed by metric name
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Profile {
+    pub id: ProfileId,
+    pub user: String,
+    pub name: String,
+    pub alpha: BTreeMap<String, f64>,
+}
+
+/// body of create and update requests
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ProfileRequest {
+    pub user: String,
+    pub name: String,
+    pub alpha: BTreeMap<String, f64>,
+}
+
+/// metrics of a graph, that do not match the profile
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct StaleGraph {
+    pub graph: String,
+    /// metrics of the graph without weight in the profile
+    pub missing: Vec<String>,
+    /// weights in the profile the graph does not know
+    pub unknown: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileResponse {
+    #[serde(flatten)]
+    pub profile: Profile,
+    pub stale: Vec<StaleGraph>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ProfileError {
+    NotFound(ProfileId),
+    Invalid(String),
+    Storage(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::NotFound(id) => write!(f, "profile {} not found", id),
+            ProfileError::Invalid(msg) => write!(f, "invalid profile: {}", msg),
+            ProfileError::Storage(msg) => write!(f, "error while storing profiles: {}", msg),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct ProfileFile {
+    profiles: Vec<Profile>,
+}
+
+/// profiles persisted to a json-file, rewritten on every change
+pub struct ProfileStore {
+    path: Option<PathBuf>,
+    profiles: RwLock<BTreeMap<ProfileId, Profile>>,
+    /// metrics of every loaded graph, profiles get validated against them
+    graph_metrics: RwLock<BTreeMap<String, Vec<String>>>,
+}
+
+impl ProfileStore {
+    /// store without file, profiles are lost on restart
+    pub fn in_memory() -> Self {
+        ProfileStore {
+            path: None,
+            profiles: RwLock::new(BTreeMap::new()),
+            graph_metrics: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// read profiles from file, a missing file starts empty
+    pub fn open(path: &str) -> Result<Self, ProfileError> {
+        let path = PathBuf::from(path);
+        let content = if path.exists() {
+            let file = File::open(&path).map_err(|e| ProfileError::Storage(e.to_string()))?;
+            serde_json::from_reader(BufReader::new(file))
+                .map_err(|e| ProfileError::Storage(e.to_string()))?
+        } else {
+            ProfileFile::default()
+        };
+        Ok(ProfileStore {
+            path: Some(path),
+            profiles: RwLock::new(
+                content
+                    .profiles
+                    .into_iter()
+                    .map(|profile| (profile.id, profile))
+                    .collect(),
+            ),
+            graph_metrics: RwLock::new(BTreeMap::new()),
+        })
+    }
+
+    /// all profiles, optionally only those of one user
+    pub fn list(&self, user: Option<&str>) -> Vec<ProfileResponse> {
+        self.profiles
+            .read()
+            .unwrap()
+            .values()
+            .filter(|profile| user.is_none() || user == Some(profile.user.as_str()))
+            .map(|profile| self.response(profile.clone()))
+            .collect()
+    }
+
+    pub fn get(&self, id: ProfileId) -> Result<Profile, ProfileError> {
+        self.profiles
+            .read()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(ProfileError::NotFound(id))
+    }
+
+    pub fn get_response(&self, id: ProfileId) -> Result<ProfileResponse, ProfileError> {
+        self.get(id).map(|profile| self.response(profile))
+    }
+
+    pub fn create(&self, request: ProfileRequest) -> Result<ProfileResponse, ProfileError> {
+        validate_request(&request)?;
+        let mut profiles = self.profiles.write().unwrap();
+        let id = profiles.keys().next_back().map_or(1, |id| id + 1);
+        let profile = Profile {
+            id,
+            user: request.user,
+            name: request.name,
+            alpha: request.alpha,
+        };
+        profiles.insert(id, profile.clone());
+        self.persist(&profiles)?;
+        Ok(self.response(profile))
+    }
+
+    pub fn update(
+        &self,
+        id: ProfileId,
+        request: ProfileRequest,
+    ) -> Result<ProfileResponse, ProfileError> {
+        validate_request(&request)?;
+        let mut profiles = self.profiles.write().unwrap();
+        let profile = profiles.get_mut(&id).ok_or(ProfileError::NotFound(id))?;
+        profile.user = request.user;
+        profile.name = request.name;
+        profile.alpha = request.alpha;
+        let profile = profile.clone();
+        self.persist(&profiles)?;
+        Ok(self.response(profile))
+    }
+
+    pub fn delete(&self, id: ProfileId) -> Result<(), ProfileError> {
+        let mut profiles = self.profiles.write().unwrap();
+        profiles.remove(&id).ok_or(ProfileError::NotFound(id))?;
+        self.persist(&profiles)
+    }
+
+    /// remember the metrics of a (re)loaded graph and return the profiles stale for it
+    pub fn graph_loaded(&self, graph: &str, metrics: &[String]) -> Vec<(ProfileId, StaleGraph)> {
+        self.graph_metrics
+            .write()
+            .unwrap()
+            .insert(graph.to_string(), metrics.to_vec());
+        self.profiles
+            .read()
+            .unwrap()
+            .values()
+            .filter_map(|profile| {
+                check_metrics(profile, graph, metrics).map(|stale| (profile.id, stale))
+            })
+            .collect()
+    }
+
+    /// alpha vector of a profile in the metric order of a graph
+    pub fn alpha_for(
+        &self,
+        id: ProfileId,
+        graph: &str,
+        metrics: &[String],
+    ) -> Result<Vec<f64>, ProfileError> {
+        let profile = self.get(id)?;
+        if let Some(stale) = check_metrics(&profile, graph, metrics) {
+            return Err(ProfileError::Invalid(format!(
+                "profile {} is stale for graph '{}' (missing: {:?}, unknown: {:?})",
+                id, graph, stale.missing, stale.unknown
+            )));
+        }
+        Ok(metrics.iter().map(|metric| profile.alpha[metric]).collect())
+    }
+
+    fn response(&self, profile: Profile) -> ProfileResponse {
+        let stale = self
+            .graph_metrics
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(graph, metrics)| check_metrics(&profile, graph, metrics))
+            .collect();
+        ProfileResponse { profile, stale }
+    }
+
+    /// write to a temporary file first, so a crash never leaves a truncated file
+    fn persist(&self, profiles: &BTreeMap<ProfileId, Profile>) -> Result<(), ProfileError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let content = ProfileFile {
+            profiles: profiles.values().cloned().collect(),
+        };
+        let output = serde_json::to_string_pretty(&content)
+            .map_err(|e| ProfileError::Storage(e.to_string()))?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, output).map_err(|e| ProfileError::Storage(e.to_string()))?;
+        fs::rename(&tmp_path, path).map_err(|e| ProfileError::Storage(e.to_string()))
+    }
+}
+
+fn validate_request(request: &ProfileRequest) -> Result<(), ProfileError> {
+    if request.user.is_empty() || request.name.is_empty() {
+        return Err(ProfileError::Invalid(
+            "user and name must not be empty".to_string(),
+        ));
+    }
+    if request.alpha.is_empty() {
+        return Err(ProfileError::Invalid("alpha is empty".to_string()));
+    }
+    if let Some((metric, _)) = request
+        .alpha
+        .iter()
+        .find(|(_, value)| !value.is_finite() || **value < 0.0)
+    {
+        return Err(ProfileError::Invalid(format!(
+            "weight of '{}' has to be a non-negative number",
+            metric
+        )));
+    }
+    Ok(())
+}
+
+/// None if the profile has a weight for exactly the metrics of the graph
+fn check_metrics(profile: &Profile, graph: &str, metrics: &[String]) -> Option<StaleGraph> {
+    let missing: Vec<String> = metrics
+        .iter()
+        .filter(|metric| !profile.alpha.contains_key(*metric))
+        .cloned()
+        .collect();
+    let unknown: Vec<String> = profile
+        .alpha
+        .keys()
+        .filter(|metric| !metrics.contains(metric))
+        .cloned()
+        .collect();
+    if missing.is_empty() && unknown.is_empty() {
+        None
+    } else {
+        Some(StaleGraph {
+            graph: graph.to_string(),
+            missing,
+            unknown,
+        })
+    }
+}
+
+#[test]
+fn profile_alpha_by_metric_order() {
+    let store = ProfileStore::in_memory();
+    let mut alpha = BTreeMap::new();
+    alpha.insert("time".to_string(), 0.75);
+    alpha.insert("distance".to_string(), 0.25);
+    let profile = store
+        .create(ProfileRequest {
+            user: "alice".to_string(),
+            name: "fast".to_string(),
+            alpha,
+        })
+        .unwrap();
+
+    let metrics = vec!["distance".to_string(), "time".to_string()];
+    assert!(store.graph_loaded("car", &metrics).is_empty());
+    assert_eq!(
+        store.alpha_for(profile.profile.id, "car", &metrics),
+        Ok(vec![0.25, 0.75])
+    );
+
+    // graph gained a metric, so the profile is stale for it
+    let metrics = vec![
+        "distance".to_string(),
+        "time".to_string(),
+        "height".to_string(),
+    ];
+    let stale = store.graph_loaded("bicycle", &metrics);
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].1.missing, vec!["height".to_string()]);
+    assert!(store
+        .alpha_for(profile.profile.id, "bicycle", &metrics)
+        .is_err());
+    assert_eq!(store.list(Some("alice"))[0].stale.len(), 1);
+    assert!(store.list(Some("bob")).is_empty());
+}
+
+#[test]
+fn invalid_profiles() {
+    let store = ProfileStore::in_memory();
+    let mut alpha = BTreeMap::new();
+    alpha.insert("time".to_string(), -1.0);
+    let request = ProfileRequest {
+        user: "alice".to_string(),
+        name: "broken".to_string(),
+        alpha,
+    };
+    assert!(matches!(
+        store.create(request),
+        Err(ProfileError::Invalid(_))
+    ));
+    assert_eq!(store.delete(1), Err(ProfileError::NotFound(1)));
+}
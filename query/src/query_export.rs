@@ -1,8 +1,50 @@
 use super::*;
 
-use std::collections::BTreeSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::Duration;
 
-pub trait Export {
+/// steps of answering a query, from clicked points to the drawn path
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// finding the closest nodes of start and end
+    Snap,
+    /// pch-search inside the cells of start and end
+    Pch,
+    /// search on the mlp-levels above
+    Overlay,
+    /// resolving shortcuts into original edges
+    Unpack,
+    /// converting edges to coordinates
+    Geometry,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Phase::Snap => "snap",
+            Phase::Pch => "pch",
+            Phase::Overlay => "overlay",
+            Phase::Unpack => "unpack",
+            Phase::Geometry => "geometry",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [
+        Phase::Snap,
+        Phase::Pch,
+        Phase::Overlay,
+        Phase::Unpack,
+        Phase::Geometry,
+    ];
+}
+
+pub trait Export: Send {
     fn new() -> Self
     where
         Self: Sized;
@@ -13,6 +55,7 @@ pub trait Export {
     fn relaxed_edge(&mut self);
     fn visited_partition(&mut self, _level_height: Level, _partition_id: PartitionId);
     fn current_meeting_point(&mut self, _node_id: NodeId);
+    fn phase_finished(&mut self, _phase: Phase, _duration: Duration);
 }
 
 #[derive(Debug)]
@@ -31,6 +74,7 @@ impl Export for NoOp {
     fn relaxed_edge(&mut self) {}
     fn visited_partition(&mut self, _level_height: Level, _partition_id: PartitionId) {}
     fn current_meeting_point(&mut self, _node_id: NodeId) {}
+    fn phase_finished(&mut self, _phase: Phase, _duration: Duration) {}
 }
 
 #[derive(Debug)]
@@ -62,6 +106,7 @@ impl Export for Counter {
     }
     fn visited_partition(&mut self, _level_height: Level, _partition_id: PartitionId) {}
     fn current_meeting_point(&mut self, _node_id: NodeId) {}
+    fn phase_finished(&mut self, _phase: Phase, _duration: Duration) {}
 }
 
 #[derive(Debug)]
@@ -69,6 +114,8 @@ pub struct RealExport {
     pub heap_pops: usize,
     pub visited_nodes: Vec<NodeId>,
     pub visited_edges: Vec<EdgeId>,
+    /// node settled via the visited edge at the same index, tells its search direction
+    pub visited_edges_settled: Vec<NodeId>,
     pub relaxed_edges: usize,
     pub visited_partitions: BTreeSet<(Level, PartitionId)>,
     pub meeting_node: Option<NodeId>,
@@ -82,6 +129,7 @@ impl Export for RealExport {
             heap_pops: 0,
             visited_nodes: Vec::new(),
             visited_edges: Vec::new(),
+            visited_edges_settled: Vec::new(),
             relaxed_edges: 0,
             visited_partitions: BTreeSet::new(),
             meeting_node: None,
@@ -91,6 +139,7 @@ impl Export for RealExport {
         self.heap_pops = 0;
         self.visited_nodes.clear();
         self.visited_edges.clear();
+        self.visited_edges_settled.clear();
         self.relaxed_edges = 0;
         self.visited_partitions.clear();
         self.meeting_node = None;
@@ -104,6 +153,9 @@ impl Export for RealExport {
     fn visited_edge(&mut self, prev_edge: Option<EdgeId>) {
         if let Some(edge_id) = prev_edge {
             self.visited_edges.push(edge_id);
+            // engines report the settled node right before its edge
+            self.visited_edges_settled
+                .push(*self.visited_nodes.last().unwrap_or(&INVALID_NODE));
         }
     }
     fn relaxed_edge(&mut self) {
@@ -116,4 +168,38 @@ impl Export for RealExport {
     fn current_meeting_point(&mut self, node_id: NodeId) {
         self.meeting_node = Some(node_id);
     }
+    fn phase_finished(&mut self, _phase: Phase, _duration: Duration) {}
+}
+
+/// summed up duration of each phase
+#[derive(Debug)]
+pub struct PhaseTimer {
+    pub phases: BTreeMap<Phase, Duration>,
+}
+impl PhaseTimer {
+    pub fn get(&self, phase: Phase) -> Duration {
+        self.phases.get(&phase).copied().unwrap_or_default()
+    }
+}
+impl Export for PhaseTimer {
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            phases: BTreeMap::new(),
+        }
+    }
+    fn reset(&mut self) {
+        self.phases.clear();
+    }
+    fn heap_pop(&mut self) {}
+    fn visited_node(&mut self, _node_id: NodeId) {}
+    fn visited_edge(&mut self, _edge_id: Option<EdgeId>) {}
+    fn relaxed_edge(&mut self) {}
+    fn visited_partition(&mut self, _level_height: Level, _partition_id: PartitionId) {}
+    fn current_meeting_point(&mut self, _node_id: NodeId) {}
+    fn phase_finished(&mut self, phase: Phase, duration: Duration) {
+        *self.phases.entry(phase).or_default() += duration;
+    }
 }
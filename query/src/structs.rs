@@ -1,6 +1,9 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::str::FromStr;
 
+use crate::alpha::Alpha;
+use crate::components::Components;
 use crate::constants::*;
 use crate::graph::Graph;
 
@@ -34,7 +37,7 @@ impl Edge {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct GridBounds {
     pub lat_amount: usize,
     pub lat_min: Angle,
@@ -57,6 +60,37 @@ pub struct BinFile {
     pub grid: Vec<NodeId>,
     pub grid_bounds: GridBounds,
     pub metrics: Vec<String>,
+    pub strong_components: Vec<ComponentId>,
+    pub weak_components: Vec<ComponentId>,
+    pub edge_orders: EdgeOrders,
+}
+
+/// edge-ids of every node in the order a query type expects them,
+/// grouped by node like `up_offset` and `down_offset`
+#[derive(Deserialize, Clone, Debug)]
+pub struct EdgeOrder {
+    pub up: Vec<EdgeId>,
+    pub down: Vec<EdgeId>,
+}
+
+/// written by the pre-processing, normal and bidirectional dijkstra share one order
+#[derive(Deserialize, Clone, Debug)]
+pub struct EdgeOrders {
+    pub normal: EdgeOrder,
+    pub pch: EdgeOrder,
+    pub pcrp: EdgeOrder,
+    pub prp: EdgeOrder,
+}
+
+impl EdgeOrders {
+    pub fn get(&self, query_type: QueryType) -> &EdgeOrder {
+        match query_type {
+            QueryType::Normal | QueryType::Bi => &self.normal,
+            QueryType::Pch => &self.pch,
+            QueryType::Pcrp => &self.pcrp,
+            QueryType::Prp => &self.prp,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -68,6 +102,7 @@ pub struct WebData {
     pub grid: Vec<NodeId>,
     pub grid_bounds: GridBounds,
     pub metrics: Vec<String>,
+    pub components: Components,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -85,10 +120,14 @@ pub struct EvalPoint {
     pub end_id: Option<NodeId>,
     pub start_pos: Location,
     pub end_pos: Location,
-    pub alpha: Vec<f64>,
+    pub alpha: Alpha,
+    /// scale alpha onto the simplex before querying
+    #[serde(default)]
+    pub normalize: bool,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum QueryType {
     Normal,
     Bi,
@@ -97,6 +136,16 @@ pub enum QueryType {
     Prp,
 }
 
+impl QueryType {
+    pub const ALL: [QueryType; 5] = [
+        QueryType::Normal,
+        QueryType::Bi,
+        QueryType::Pch,
+        QueryType::Pcrp,
+        QueryType::Prp,
+    ];
+}
+
 impl FromStr for QueryType {
     type Err = &'static str;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -110,3 +159,16 @@ impl FromStr for QueryType {
         }
     }
 }
+
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            QueryType::Normal => "normal",
+            QueryType::Bi => "bi",
+            QueryType::Pch => "pch",
+            QueryType::Pcrp => "pcrp",
+            QueryType::Prp => "prp",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+use serde::{Deserialize, Serialize};
+
+/// cost of one metric on the chosen and on the alternative route
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct MetricDifference {
+    pub metric: String,
+    pub chosen: Cost,
+    pub alternative: Cost,
+    /// how much more (or less) the chosen route costs in percent, None if the alternative is 0
+    pub percent: Option<f64>,
+}
+
+/// chosen route compared to the optimal route of a single metric
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Tradeoff {
+    pub optimized_metric: String,
+    pub same_route: bool,
+    pub differences: Vec<MetricDifference>,
+    /// e.g. "+7% time, −40% ascent, +2% distance"
+    pub summary: String,
+}
+
+/// alpha optimizing only a single metric
+pub fn unit_alpha(metric: usize, dim: usize) -> Vec<f64> {
+    let mut alpha = vec![0.0; dim];
+    alpha[metric] = 1.0;
+    alpha
+}
+
+/// compare the metric costs of the chosen route against an alternative
+pub fn compare(
+    optimized_metric: &str,
+    chosen: &[Cost],
+    alternative: &[Cost],
+    same_route: bool,
+    metrics: &[String],
+) -> Tradeoff {
+    let differences: Vec<MetricDifference> = metrics
+        .iter()
+        .zip(chosen.iter().zip(alternative))
+        .map(|(metric, (chosen, alternative))| MetricDifference {
+            metric: metric.clone(),
+            chosen: *chosen,
+            alternative: *alternative,
+            percent: percent_difference(*chosen, *alternative),
+        })
+        .collect();
+    Tradeoff {
+        optimized_metric: optimized_metric.to_string(),
+        same_route,
+        summary: summarize(&differences),
+        differences,
+    }
+}
+
+fn percent_difference(chosen: Cost, alternative: Cost) -> Option<f64> {
+    if alternative == 0.0 {
+        if chosen == 0.0 {
+            Some(0.0)
+        } else {
+            None
+        }
+    } else {
+        Some((chosen - alternative) / alternative * 100.0)
+    }
+}
+
+fn summarize(differences: &[MetricDifference]) -> String {
+    differences
+        .iter()
+        .map(|difference| match difference.percent {
+            Some(percent) => {
+                let rounded = percent.round();
+                let sign = if rounded > 0.0 {
+                    "+"
+                } else if rounded < 0.0 {
+                    "\u{2212}"
+                } else {
+                    "\u{b1}"
+                };
+                format!("{}{}% {}", sign, rounded.abs(), difference.metric)
+            }
+            None => format!("+{:.2} {}", difference.chosen, difference.metric),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[test]
+fn tradeoff_summary() {
+    let metrics = vec![
+        "time".to_string(),
+        "ascent".to_string(),
+        "distance".to_string(),
+    ];
+    let tradeoff = compare(
+        "time",
+        &[107.0, 60.0, 51.0],
+        &[100.0, 100.0, 50.0],
+        false,
+        &metrics,
+    );
+    assert_eq!(
+        tradeoff.summary,
+        "+7% time, \u{2212}40% ascent, +2% distance"
+    );
+    assert_eq!(tradeoff.differences[1].alternative, 100.0);
+    assert_eq!(unit_alpha(1, 3), vec![0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn tradeoff_without_alternative_costs() {
+    let metrics = vec!["time".to_string(), "ascent".to_string()];
+    let tradeoff = compare("time", &[10.0, 0.0], &[10.0, 0.0], true, &metrics);
+    assert_eq!(tradeoff.summary, "\u{b1}0% time, \u{b1}0% ascent");
+
+    let tradeoff = compare("time", &[10.0, 3.0], &[8.0, 0.0], false, &metrics);
+    assert_eq!(tradeoff.differences[1].percent, None);
+    assert_eq!(tradeoff.summary, "+25% time, +3.00 ascent");
+}
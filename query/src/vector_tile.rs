@@ -0,0 +1,385 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// resolution of the tile coordinates
+pub const EXTENT: u32 = 4096;
+
+// layers are left out below their minimum zoom, so low zooms stay cheap
+pub const CELLS_MIN_ZOOM: u32 = 10;
+pub const SHORTCUTS_MIN_ZOOM: u32 = 12;
+pub const EDGES_MIN_ZOOM: u32 = 13;
+pub const NODES_MIN_ZOOM: u32 = 14;
+pub const MAX_ZOOM: u32 = 22;
+
+// protobuf wire types
+const WIRE_VARINT: u32 = 0;
+const WIRE_LENGTH: u32 = 2;
+
+// geometry types and commands of the mapbox vector tile spec
+const GEOM_POINT: u32 = 1;
+const GEOM_LINESTRING: u32 = 2;
+const COMMAND_MOVE_TO: u32 = 1;
+const COMMAND_LINE_TO: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileId {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    /// None if the tile does not exist at this zoom
+    pub fn new(z: u32, x: u32, y: u32) -> Option<Self> {
+        if z > MAX_ZOOM || x >= 1 << z || y >= 1 << z {
+            return None;
+        }
+        Some(TileId { z, x, y })
+    }
+
+    /// south-west and north-east corner of the tile
+    pub fn bounds(&self) -> (Location, Location) {
+        let n = (1u64 << self.z) as f64;
+        let longitude = |x: f64| x / n * 360.0 - 180.0;
+        let latitude = |y: f64| (PI * (1.0 - 2.0 * y / n)).sinh().atan().to_degrees();
+        (
+            Location {
+                latitude: latitude(self.y as f64 + 1.0),
+                longitude: longitude(self.x as f64),
+            },
+            Location {
+                latitude: latitude(self.y as f64),
+                longitude: longitude(self.x as f64 + 1.0),
+            },
+        )
+    }
+
+    /// web-mercator projection into tile coordinates, may exceed the extent
+    pub fn project(&self, latitude: Angle, longitude: Angle) -> (i32, i32) {
+        let n = (1u64 << self.z) as f64;
+        let x = (longitude + 180.0) / 360.0 * n - self.x as f64;
+        // web-mercator is undefined at the poles
+        let lat = latitude.clamp(-85.051_128_78, 85.051_128_78).to_radians();
+        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * n - self.y as f64;
+        (
+            (x * EXTENT as f64).round() as i32,
+            (y * EXTENT as f64).round() as i32,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Value {
+    String(String),
+    UInt(u64),
+    Bool(bool),
+}
+
+impl Value {
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        match self {
+            Value::String(value) => write_bytes(&mut buffer, 1, value.as_bytes()),
+            Value::UInt(value) => {
+                write_tag(&mut buffer, 5, WIRE_VARINT);
+                write_varint(&mut buffer, *value);
+            }
+            Value::Bool(value) => {
+                write_tag(&mut buffer, 7, WIRE_VARINT);
+                write_varint(&mut buffer, *value as u64);
+            }
+        }
+        buffer
+    }
+}
+
+/// a single named layer of a vector tile, keys and values are deduplicated
+#[derive(Debug, Clone)]
+pub struct Layer {
+    name: String,
+    keys: Vec<String>,
+    key_ids: HashMap<String, u32>,
+    values: Vec<Value>,
+    value_ids: HashMap<Value, u32>,
+    features: Vec<Vec<u8>>,
+}
+
+impl Layer {
+    pub fn new(name: &str) -> Self {
+        Layer {
+            name: name.to_string(),
+            keys: Vec::new(),
+            key_ids: HashMap::new(),
+            values: Vec::new(),
+            value_ids: HashMap::new(),
+            features: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn add_point(&mut self, id: u64, point: (i32, i32), properties: Vec<(&str, Value)>) {
+        let geometry = vec![
+            command(COMMAND_MOVE_TO, 1),
+            zigzag(point.0),
+            zigzag(point.1),
+        ];
+        self.add_feature(id, GEOM_POINT, geometry, properties);
+    }
+
+    pub fn add_line_string(
+        &mut self,
+        id: u64,
+        points: &[(i32, i32)],
+        properties: Vec<(&str, Value)>,
+    ) {
+        if points.len() < 2 {
+            return;
+        }
+        // coordinates are relative to the previous point
+        let mut geometry = Vec::with_capacity(2 * points.len() + 2);
+        geometry.push(command(COMMAND_MOVE_TO, 1));
+        geometry.push(zigzag(points[0].0));
+        geometry.push(zigzag(points[0].1));
+        geometry.push(command(COMMAND_LINE_TO, points.len() as u32 - 1));
+        for (prev, next) in points.iter().zip(points.iter().skip(1)) {
+            geometry.push(zigzag(next.0.wrapping_sub(prev.0)));
+            geometry.push(zigzag(next.1.wrapping_sub(prev.1)));
+        }
+        self.add_feature(id, GEOM_LINESTRING, geometry, properties);
+    }
+
+    fn add_feature(
+        &mut self,
+        id: u64,
+        geometry_type: u32,
+        geometry: Vec<u32>,
+        properties: Vec<(&str, Value)>,
+    ) {
+        let mut tags = Vec::with_capacity(2 * properties.len());
+        for (key, value) in properties {
+            tags.push(self.key_id(key));
+            tags.push(self.value_id(value));
+        }
+        let mut feature = Vec::new();
+        write_tag(&mut feature, 1, WIRE_VARINT);
+        write_varint(&mut feature, id);
+        write_packed(&mut feature, 2, &tags);
+        write_tag(&mut feature, 3, WIRE_VARINT);
+        write_varint(&mut feature, geometry_type as u64);
+        write_packed(&mut feature, 4, &geometry);
+        self.features.push(feature);
+    }
+
+    fn key_id(&mut self, key: &str) -> u32 {
+        if let Some(id) = self.key_ids.get(key) {
+            return *id;
+        }
+        let id = self.keys.len() as u32;
+        self.keys.push(key.to_string());
+        self.key_ids.insert(key.to_string(), id);
+        id
+    }
+
+    fn value_id(&mut self, value: Value) -> u32 {
+        if let Some(id) = self.value_ids.get(&value) {
+            return *id;
+        }
+        let id = self.values.len() as u32;
+        self.values.push(value.clone());
+        self.value_ids.insert(value, id);
+        id
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_tag(&mut buffer, 15, WIRE_VARINT);
+        write_varint(&mut buffer, 2);
+        write_bytes(&mut buffer, 1, self.name.as_bytes());
+        for feature in &self.features {
+            write_bytes(&mut buffer, 2, feature);
+        }
+        for key in &self.keys {
+            write_bytes(&mut buffer, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            write_bytes(&mut buffer, 4, &value.encode());
+        }
+        write_tag(&mut buffer, 5, WIRE_VARINT);
+        write_varint(&mut buffer, EXTENT as u64);
+        buffer
+    }
+}
+
+/// encode layers into a tile, empty layers are left out
+pub fn encode_tile(layers: &[Layer]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    for layer in layers.iter().filter(|layer| !layer.is_empty()) {
+        write_bytes(&mut buffer, 3, &layer.encode());
+    }
+    buffer
+}
+
+/// build the debug layers of a graph for one tile
+///
+/// - `edges`: original edges with their mlp-level
+/// - `shortcuts`: shortcuts with their contraction level
+/// - `nodes`: nodes with rank and partition on each mlp-level
+/// - `cells`: original edges crossing cell boundaries, `level` is the highest crossed level
+pub fn graph_tile(tile: &TileId, data: &WebData) -> Vec<u8> {
+    if tile.z < CELLS_MIN_ZOOM {
+        return encode_tile(&[]);
+    }
+    let (min, max) = tile.bounds();
+    let node_ids = grid::get_points_in_bounds(
+        &min,
+        &max,
+        &data.nodes,
+        &data.grid,
+        &data.grid_offset,
+        &data.grid_bounds,
+    );
+    let project = |node_id: NodeId| {
+        let node = &data.nodes[node_id];
+        tile.project(node.latitude, node.longitude)
+    };
+
+    let mut nodes = Layer::new("nodes");
+    let partition_keys: Vec<String> = (0..data.mlp_levels.len())
+        .map(|level| format!("partition_{}", level))
+        .collect();
+    if tile.z >= NODES_MIN_ZOOM {
+        for node_id in &node_ids {
+            let mut properties = vec![("rank", Value::UInt(data.nodes[*node_id].rank as u64))];
+            let partitions =
+                mlp_helper::get_node_partitions(*node_id, &data.nodes, &data.mlp_levels);
+            for (key, partition) in partition_keys.iter().zip(partitions.iter()) {
+                properties.push((key, Value::UInt(*partition as u64)));
+            }
+            nodes.add_point(*node_id as u64, project(*node_id), properties);
+        }
+    }
+
+    // every edge touching the tile
+    let mut edge_ids: Vec<EdgeId> = node_ids
+        .iter()
+        .flat_map(|node_id| data.graph.get_all_edge_ids(*node_id))
+        .collect();
+    edge_ids.sort_unstable();
+    edge_ids.dedup();
+
+    let mut edges = Layer::new("edges");
+    let mut shortcuts = Layer::new("shortcuts");
+    let mut cells = Layer::new("cells");
+    for edge_id in edge_ids {
+        let edge = data.graph.get_edge(edge_id);
+        let points = [project(edge.from), project(edge.to)];
+        let level = Value::UInt(edge.level.unwrap_or(INVALID_LEVEL) as u64);
+        if edge.contracted_edges.is_some() {
+            if tile.z >= SHORTCUTS_MIN_ZOOM {
+                shortcuts.add_line_string(edge_id as u64, &points, vec![("level", level)]);
+            }
+            continue;
+        }
+        if tile.z >= EDGES_MIN_ZOOM {
+            edges.add_line_string(edge_id as u64, &points, vec![("level", level)]);
+        }
+        let common_level = mlp_helper::get_highest_differing_level(
+            edge.from,
+            edge.to,
+            &data.nodes,
+            &data.mlp_levels,
+        );
+        if common_level > 0 {
+            cells.add_line_string(
+                edge_id as u64,
+                &points,
+                vec![("level", Value::UInt(common_level as u64 - 1))],
+            );
+        }
+    }
+    encode_tile(&[cells, shortcuts, edges, nodes])
+}
+
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn write_tag(buffer: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buffer, ((field << 3) | wire_type) as u64);
+}
+
+fn write_bytes(buffer: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_tag(buffer, field, WIRE_LENGTH);
+    write_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+fn write_packed(buffer: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len());
+    for value in values {
+        write_varint(&mut packed, *value as u64);
+    }
+    write_bytes(buffer, field, &packed);
+}
+
+#[test]
+fn encode_primitives() {
+    let mut buffer = Vec::new();
+    write_varint(&mut buffer, 300);
+    assert_eq!(buffer, vec![0xac, 0x02]);
+
+    assert_eq!(zigzag(0), 0);
+    assert_eq!(zigzag(-1), 1);
+    assert_eq!(zigzag(1), 2);
+    assert_eq!(zigzag(-2), 3);
+    assert_eq!(command(COMMAND_MOVE_TO, 1), 9);
+    assert_eq!(command(COMMAND_LINE_TO, 3), 26);
+}
+
+#[test]
+fn encode_line_string() {
+    // example from the vector tile specification
+    let mut layer = Layer::new("lines");
+    layer.add_line_string(
+        1,
+        &[(2, 2), (2, 10), (10, 10)],
+        vec![("level", Value::UInt(1))],
+    );
+    let feature = &layer.features[0];
+    let geometry = [9, 4, 4, 18, 0, 16, 16, 0];
+    assert!(feature.ends_with(&[&[0x22, geometry.len() as u8][..], &geometry[..]].concat()));
+    assert_eq!(layer.keys, vec!["level".to_string()]);
+    assert_eq!(layer.values, vec![Value::UInt(1)]);
+}
+
+#[test]
+fn tile_projection() {
+    let tile = TileId::new(1, 1, 0).unwrap();
+    let (min, max) = tile.bounds();
+    assert!((min.longitude - 0.0).abs() < 1e-9);
+    assert!((max.longitude - 180.0).abs() < 1e-9);
+    assert!((min.latitude - 0.0).abs() < 1e-9);
+    assert_eq!(tile.project(0.0, 0.0), (0, EXTENT as i32));
+    assert!(TileId::new(1, 2, 0).is_none());
+}